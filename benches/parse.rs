@@ -0,0 +1,70 @@
+//! Parse and serialize benchmarks, run with `cargo bench`
+
+use std::hint::black_box;
+
+use bsp::{Bsp, BspBuilder, LumpMetadata};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+/// Written maps of a sparse and a dense lump directory
+fn maps() -> [(&'static str, Vec<u8>); 2] {
+    let metadata = LumpMetadata {
+        version: 0,
+        identifier: [0; 4],
+    };
+
+    // Typical layout: a few large lumps, most of the directory empty
+    let sparse = [
+        (0, 64 * 1024),
+        (1, 32 * 1024),
+        (3, 256 * 1024),
+        (7, 512 * 1024),
+        (12, 128 * 1024),
+        (13, 256 * 1024),
+    ]
+    .into_iter()
+    .fold(
+        BspBuilder::new(*b"VBSP", 20),
+        |builder, (index, len): (usize, _)| builder.set_lump(index, metadata, vec![0xAB; len]),
+    )
+    .build()
+    .write_to_vec();
+
+    // Worst case for the directory: every lump present
+    let dense = (0..64)
+        .fold(BspBuilder::new(*b"VBSP", 20), |builder, index: usize| {
+            builder.set_lump(index, metadata, vec![0xCD; 1024])
+        })
+        .build()
+        .write_to_vec();
+
+    [("sparse", sparse), ("dense", dense)]
+}
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for (name, data) in &maps() {
+        group.bench_with_input(BenchmarkId::from_parameter(name), data, |b, data| {
+            b.iter(|| Bsp::parse(black_box(data)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn serialize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("serialize");
+    for (name, data) in &maps() {
+        let bsp = Bsp::parse(data).unwrap();
+        group.bench_function(BenchmarkId::new("write_to_vec", name), |b| {
+            b.iter(|| bsp.write_to_vec())
+        });
+
+        let mut buf = vec![0; data.len()];
+        group.bench_function(BenchmarkId::new("write_to_slice", name), |b| {
+            b.iter(|| bsp.write_to_slice(black_box(&mut buf)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parse, serialize);
+criterion_main!(benches);
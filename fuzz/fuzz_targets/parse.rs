@@ -0,0 +1,19 @@
+#![no_main]
+
+use std::io::Cursor;
+
+use bsp::{AnyBsp, Bsp};
+use libfuzzer_sys::fuzz_target;
+
+// Parsing arbitrary bytes must return `Ok` or `Err`, never panic
+fuzz_target!(|data: &[u8]| {
+    if let Ok(bsp) = Bsp::parse(data) {
+        // Every lump the directory accepted must be readable
+        for (_, (_, lump)) in bsp.lumps_iter() {
+            let _ = lump.len();
+        }
+        let _ = bsp.planes();
+    }
+    let _ = AnyBsp::parse(data);
+    let _ = Bsp::parse_reader(Cursor::new(data));
+});
@@ -1,21 +1,154 @@
-use bsp::{Bsp, LUMP_DEF_COUNT};
+use bsp::{Bsp, LumpData, LumpId, OwnedBsp, Plane, LUMP_DEF_COUNT};
 
-fn main() {
-    let Some(path) = std::env::args().nth(1) else {
-        eprintln!("Usage: ./{} <file>", env!("CARGO_BIN_NAME"));
-        std::process::exit(1);
+/// What to print about the file
+enum Mode {
+    /// Header, plane count and lump directory
+    Summary,
+    /// Lump directory as JSON
+    Json,
+    /// Bytes of a single lump
+    Lump {
+        /// Lump index
+        index: usize,
+        /// Print offsets and ASCII alongside the bytes
+        hex: bool,
+        /// Maximum number of bytes to print
+        limit: Option<usize>,
+    },
+}
+
+fn usage() -> ! {
+    eprintln!(
+        "Usage: ./{} <file> [--json | --lump <n> [--hex] [--limit <bytes>]]",
+        env!("CARGO_BIN_NAME")
+    );
+    std::process::exit(1);
+}
+
+fn parse_args() -> (String, Mode) {
+    let mut path = None;
+    let mut json = false;
+    let mut lump = None;
+    let mut hex = false;
+    let mut limit = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--lump" => match args.next().and_then(|n| n.parse().ok()) {
+                Some(index) if index < LUMP_DEF_COUNT => lump = Some(index),
+                _ => usage(),
+            },
+            "--hex" => hex = true,
+            "--limit" => match args.next().and_then(|n| n.parse().ok()) {
+                Some(bytes) => limit = Some(bytes),
+                None => usage(),
+            },
+            _ if path.is_none() && !arg.starts_with("--") => path = Some(arg),
+            _ => usage(),
+        }
+    }
+
+    let mode = match (json, lump) {
+        (false, None) if !hex && limit.is_none() => Mode::Summary,
+        (true, None) if !hex && limit.is_none() => Mode::Json,
+        (false, Some(index)) => Mode::Lump { index, hex, limit },
+        _ => usage(),
     };
+    (path.unwrap_or_else(|| usage()), mode)
+}
+
+fn main() {
+    let (path, mode) = parse_args();
 
-    println!("reading path: {path}");
+    if let Mode::Summary = mode {
+        println!("reading path: {path}");
+    }
     let contents = std::fs::read(path).expect("failed to open file");
 
-    let bsp = Bsp::parse(&contents).expect("failed to deserialize bsp");
+    let bsp = OwnedBsp::from_vec(contents).expect("failed to deserialize bsp");
+    bsp.with(|bsp| match mode {
+        Mode::Summary => print_summary(bsp),
+        Mode::Json => print_json(bsp),
+        Mode::Lump { index, hex, limit } => print_lump(bsp, index, hex, limit),
+    });
+}
+
+fn print_summary(bsp: &Bsp) {
     println!("{bsp:#?}");
 
-    for i in 0..LUMP_DEF_COUNT {
-        let (metadata, lump) = bsp.lump(i);
+    match bsp.planes() {
+        Ok(planes) => println!("planes: {}", planes.len()),
+        Err(_) => println!(
+            "planes: malformed lump ({} bytes, expected a multiple of {})",
+            bsp.lump_len(LumpId::Planes).unwrap_or_default(),
+            size_of::<Plane>()
+        ),
+    }
+
+    for (i, (metadata, data)) in bsp.lumps_iter() {
+        let name = LumpId::try_from(i)
+            .map(|id| format!(" ({id:?})"))
+            .unwrap_or_default();
+
+        println!(
+            "lump {i}{name}: {} bytes, version {}, identifier {:?}",
+            data.len(),
+            metadata.version,
+            metadata.identifier
+        );
+    }
+}
+
+#[cfg(feature = "serde")]
+fn print_json(bsp: &Bsp) {
+    println!("{}", bsp.directory_json());
+}
+
+#[cfg(not(feature = "serde"))]
+fn print_json(_: &Bsp) {
+    eprintln!("--json requires the `serde` feature");
+    std::process::exit(1);
+}
+
+#[cfg(feature = "lzma")]
+fn lump_data<'b>(bsp: &'b Bsp, index: usize) -> LumpData<'b> {
+    bsp.lump_decompressed(index)
+        .expect("failed to decompress lump")
+}
+
+#[cfg(not(feature = "lzma"))]
+fn lump_data<'b>(bsp: &'b Bsp, index: usize) -> LumpData<'b> {
+    let (_, data) = bsp.lump(index);
+    LumpData::Raw(std::cell::Ref::map(data, |data| &**data))
+}
+
+fn print_lump(bsp: &Bsp, index: usize, hex: bool, limit: Option<usize>) {
+    let data = lump_data(bsp, index);
+    let data = &data[..limit.unwrap_or(usize::MAX).min(data.len())];
+
+    for (i, line) in data.chunks(16).enumerate() {
+        let bytes: Vec<_> = line.iter().map(|b| format!("{b:02x}")).collect();
+        if !hex {
+            println!("{}", bytes.join(" "));
+            continue;
+        }
 
-        println!("lump {i}: {} bytes", lump.len());
-        println!("metadata: {metadata:#?}");
+        // Classic `offset  hex  |ascii|` layout, split into two columns of 8 bytes
+        let (left, right) = bytes.split_at(bytes.len().min(8));
+        let ascii: String = line
+            .iter()
+            .map(|&b| match b {
+                0x20..=0x7e => b as char,
+                _ => '.',
+            })
+            .collect();
+        println!(
+            "{:08x}  {:<23}  {:<23}  |{ascii}|",
+            i * 16,
+            left.join(" "),
+            right.join(" ")
+        );
     }
 }
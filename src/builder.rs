@@ -0,0 +1,94 @@
+use alloc::{
+    borrow::{Borrow, Cow},
+    vec::Vec,
+};
+
+use zerocopy::FromZeros;
+
+use crate::{
+    game_lump, Bsp, Header, LumpCell, LumpId, LumpMetadata, LUMP_ALIGNMENT, LUMP_DEF_COUNT,
+};
+
+impl Bsp<'_> {
+    /// `VBSP` file of the given format version with every lump empty
+    pub fn empty(version: u32) -> Bsp<'static> {
+        BspBuilder::new(*b"VBSP", version).build()
+    }
+}
+
+/// Builder for a [`Bsp`] that owns all of its data, for authoring files from scratch
+///
+/// Lumps that aren't set are left empty with zeroed metadata.
+#[derive(Debug, Clone)]
+pub struct BspBuilder {
+    header: Header,
+    lumps: [(LumpMetadata, Vec<u8>); LUMP_DEF_COUNT],
+}
+
+impl BspBuilder {
+    /// Start a file with the given identifier (usually `VBSP`) and format version
+    pub fn new(identifier: [u8; 4], version: u32) -> Self {
+        let mut header = Header::new_zeroed();
+        header.identifier = identifier;
+        header.version = version;
+
+        Self {
+            header,
+            lumps: core::array::from_fn(|_| (LumpMetadata::new_zeroed(), Vec::new())),
+        }
+    }
+
+    /// Set the file revision
+    pub fn revision(mut self, revision: i32) -> Self {
+        self.header.revision = revision;
+        self
+    }
+
+    /// Set the metadata and data of the lump at `index`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of range.
+    pub fn set_lump<I>(mut self, index: I, metadata: LumpMetadata, data: Vec<u8>) -> Self
+    where
+        I: Into<usize>,
+    {
+        self.lumps[index.into()] = (metadata, data);
+        self
+    }
+
+    /// Build the [`Bsp`]
+    ///
+    /// The header's lump definitions are filled in with the lumps laid out as
+    /// [`Bsp::write_to_io`] would write them.
+    ///
+    /// Offsets in the [`LumpId::GameLump`] directory are given relative to the start of the
+    /// lump, and made absolute once its place in the file is known.
+    pub fn build(self) -> Bsp<'static> {
+        let lumps = self
+            .lumps
+            .map(|(metadata, data)| LumpCell::new(Cow::Owned(metadata), Cow::Owned(data)));
+        let mut bsp = Bsp {
+            header: Cow::Owned(self.header),
+            lumps,
+        };
+
+        let lumps: Vec<_> = bsp.lump_iter().collect();
+        let header = bsp.packed_header(
+            lumps
+                .iter()
+                .map(|(metadata, data)| (*metadata.borrow().as_ref(), data.as_ref())),
+            LUMP_ALIGNMENT,
+        );
+        drop(lumps);
+
+        // Game lump offsets were relative to the start of the lump under the zeroed header
+        let game_lump = LumpId::GameLump as usize;
+        let shift = bsp.lump_shift(game_lump, header.lump_defs[game_lump].offset);
+        let (_, data) = bsp.lumps[game_lump].lump.get_mut();
+        game_lump::shift_offsets(data.to_mut(), shift);
+
+        bsp.header = Cow::Owned(header);
+        bsp
+    }
+}
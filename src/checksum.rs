@@ -0,0 +1,68 @@
+use crate::{Bsp, LumpId};
+
+impl Bsp<'_> {
+    /// CRC-32 of the data of the lump at `index`, or `None` if it's out of range
+    ///
+    /// Lumps are hashed as stored, so compressed lumps aren't decompressed first.
+    pub fn lump_crc32<I>(&self, index: I) -> Option<u32>
+    where
+        I: Into<usize>,
+    {
+        let (_, data) = self.try_lump(index)?;
+        Some(crc32fast::hash(&data))
+    }
+
+    /// Map CRC-32 as computed by the engine when loading a map
+    ///
+    /// Every lump but [`LumpId::Entities`] is hashed as stored, in directory order. The
+    /// header is left out, so moving lumps around doesn't change the result.
+    pub fn content_crc32(&self) -> u32 {
+        let mut hasher = crc32fast::Hasher::new();
+        for (index, (_, data)) in self.lumps_iter() {
+            if index != usize::from(LumpId::Entities) {
+                hasher.update(&data);
+            }
+        }
+        hasher.finalize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use crate::{Bsp, BspBuilder, LumpId, LumpMetadata};
+
+    fn synthetic() -> Bsp<'static> {
+        let metadata = LumpMetadata {
+            version: 0,
+            identifier: [0; 4],
+        };
+        BspBuilder::new(*b"VBSP", 20)
+            .set_lump(LumpId::Entities, metadata, b"{ }\0".to_vec())
+            .set_lump(LumpId::Planes, metadata, b"hello".to_vec())
+            .set_lump(LumpId::Vertexes, metadata, b" world".to_vec())
+            .build()
+    }
+
+    #[test]
+    fn pinned_crc32() {
+        let bsp = synthetic();
+        // CRC-32 (IEEE) of "hello world", skipping the entity lump
+        assert_eq!(bsp.content_crc32(), 0x0d4a_1185);
+        assert_eq!(bsp.lump_crc32(LumpId::Planes), Some(0x3610_a686));
+        assert_eq!(bsp.lump_crc32(64usize), None);
+    }
+
+    #[test]
+    fn crc32_ignores_layout_and_entities() {
+        let bsp = synthetic();
+        let expected = bsp.content_crc32();
+
+        let mut written = Vec::new();
+        bsp.write_to_io_aligned(&mut written, 64).unwrap();
+        let reparsed = Bsp::parse(&written).unwrap();
+        reparsed.set_entities(&[]);
+        assert_eq!(reparsed.content_crc32(), expected);
+    }
+}
@@ -0,0 +1,106 @@
+use alloc::{format, vec::Vec};
+
+use crate::Bsp;
+
+/// Structural differences between two [`Bsp`]s, see [`Bsp::diff`]
+///
+/// Lump offsets aren't compared, so files that only differ in layout are equivalent.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BspDiff {
+    /// File identifiers, if they differ
+    pub identifier: Option<([u8; 4], [u8; 4])>,
+    /// File format versions, if they differ
+    pub version: Option<(u32, u32)>,
+    /// File revisions, if they differ
+    pub revision: Option<(i32, i32)>,
+    /// Lumps that differ, in index order
+    pub lumps: Vec<LumpDiff>,
+}
+
+/// Differences between the same lump of two [`Bsp`]s
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LumpDiff {
+    /// Lump index
+    pub index: usize,
+    /// Whether the data is byte-for-byte identical
+    pub identical: bool,
+    /// Length of the other lump minus the length of this one
+    pub len_delta: i64,
+    /// Lump versions, if they differ
+    pub version: Option<(u32, u32)>,
+}
+
+impl BspDiff {
+    /// Whether the files are structurally equivalent
+    pub fn is_empty(&self) -> bool {
+        self.identifier.is_none()
+            && self.version.is_none()
+            && self.revision.is_none()
+            && self.lumps.is_empty()
+    }
+}
+
+impl core::fmt::Display for BspDiff {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.is_empty() {
+            return f.write_str("no differences");
+        }
+
+        let mut lines = Vec::new();
+        if let Some((a, b)) = self.identifier {
+            lines.push(format!("identifier: {a:?} -> {b:?}"));
+        }
+        if let Some((a, b)) = self.version {
+            lines.push(format!("version: {a} -> {b}"));
+        }
+        if let Some((a, b)) = self.revision {
+            lines.push(format!("revision: {a} -> {b}"));
+        }
+        for lump in &self.lumps {
+            let mut line = format!("lump {}:", lump.index);
+            if !lump.identical {
+                line += &format!(" data changed ({:+} bytes)", lump.len_delta);
+            }
+            if let Some((a, b)) = lump.version {
+                line += &format!(" version {a} -> {b}");
+            }
+            lines.push(line);
+        }
+        f.write_str(&lines.join("\n"))
+    }
+}
+
+/// `Some((a, b))` if they differ
+fn changed<T: PartialEq>(a: T, b: T) -> Option<(T, T)> {
+    (a != b).then_some((a, b))
+}
+
+impl Bsp<'_> {
+    /// Compare the header fields and every lump's data and version against `other`
+    ///
+    /// # Panics
+    ///
+    /// Panics if any lump of either file is currently borrowed mutably.
+    pub fn diff(&self, other: &Bsp<'_>) -> BspDiff {
+        let lumps = self
+            .lumps_iter()
+            .zip(other.lumps_iter())
+            .filter_map(|((index, (meta_a, data_a)), (_, (meta_b, data_b)))| {
+                let diff = LumpDiff {
+                    index,
+                    identical: **data_a == **data_b,
+                    len_delta: data_b.len() as i64 - data_a.len() as i64,
+                    version: changed(meta_a.version, meta_b.version),
+                };
+                (!diff.identical || diff.version.is_some()).then_some(diff)
+            })
+            .collect();
+
+        BspDiff {
+            identifier: changed(self.header.identifier, other.header.identifier),
+            version: changed(self.header.version, other.header.version),
+            revision: changed(self.header.revision, other.header.revision),
+            lumps,
+        }
+    }
+}
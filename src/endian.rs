@@ -0,0 +1,313 @@
+use alloc::borrow::Cow;
+
+use zerocopy::{FromBytes, IntoBytes};
+
+use crate::{
+    AreaPortal, BrushSide, Bsp, BspFlavor, CubemapSample, DispInfo, Face, Header, Leaf, LeafV0,
+    LumpCell, LumpId, Node, Overlay, ParseError, Primitive, OVERLAY_MAX_FACES,
+};
+
+/// Byte order of a BSP file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Endianness {
+    /// PC files
+    Little,
+    /// Console files, such as those of Xbox 360 and PS3 ports
+    Big,
+}
+
+impl Endianness {
+    /// Identify the byte order from the file magic, or `None` if it isn't a known
+    /// [`BspFlavor`] either way
+    pub fn detect(data: &[u8]) -> Option<Self> {
+        let magic = *data.first_chunk::<4>()?;
+        let mut swapped = magic;
+        swapped.reverse();
+
+        if BspFlavor::from_magic(magic).is_known() {
+            Some(Self::Little)
+        } else if BspFlavor::from_magic(swapped).is_known() {
+            Some(Self::Big)
+        } else {
+            None
+        }
+    }
+}
+
+/// Run of `count` fields that are each `width` bytes wide
+type Run = (usize, usize);
+
+/// Size of a record made of `layout`
+const fn layout_size(layout: &[Run]) -> usize {
+    let mut size = 0;
+    let mut i = 0;
+    while i < layout.len() {
+        size += layout[i].0 * layout[i].1;
+        i += 1;
+    }
+    size
+}
+
+/// Records made only of 4-byte fields, swapped a word at a time
+const WORDS: &[Run] = &[(4, 1)];
+/// Records made only of 2-byte fields
+const HALVES: &[Run] = &[(2, 1)];
+const FACE: &[Run] = &[
+    (2, 1),
+    (1, 2),
+    (4, 1),
+    (2, 4),
+    (1, 4),
+    (4, 7),
+    (2, 2),
+    (4, 1),
+];
+const NODE: &[Run] = &[(4, 3), (2, 10)];
+const LEAF: &[Run] = &[(4, 1), (2, 14)];
+const LEAF_V0: &[Run] = &[(4, 1), (2, 13), (1, 24), (2, 1)];
+const BRUSH_SIDE: &[Run] = &[(2, 4)];
+const AREA_PORTAL: &[Run] = &[(2, 4), (4, 1)];
+const PRIMITIVE: &[Run] = &[(1, 2), (2, 4)];
+const CUBEMAP: &[Run] = &[(4, 3), (1, 4)];
+const OVERLAY: &[Run] = &[(4, 1), (2, 2), (4, OVERLAY_MAX_FACES + 2 + 2 + 12 + 3 + 3)];
+#[rustfmt::skip]
+const DISP_INFO: &[Run] = &[
+    (4, 9), (2, 2), (4, 2),
+    // Edge neighbors, two sub-neighbors each
+    (2, 1), (1, 4), (2, 1), (1, 4), (2, 1), (1, 4), (2, 1), (1, 4),
+    (2, 1), (1, 4), (2, 1), (1, 4), (2, 1), (1, 4), (2, 1), (1, 4),
+    // Corner neighbors
+    (2, 4), (1, 2), (2, 4), (1, 2), (2, 4), (1, 2), (2, 4), (1, 2),
+    (4, 10),
+];
+
+const _: () = {
+    assert!(layout_size(FACE) == size_of::<Face>());
+    assert!(layout_size(NODE) == size_of::<Node>());
+    assert!(layout_size(LEAF) == size_of::<Leaf>());
+    assert!(layout_size(LEAF_V0) == size_of::<LeafV0>());
+    assert!(layout_size(BRUSH_SIDE) == size_of::<BrushSide>());
+    assert!(layout_size(AREA_PORTAL) == size_of::<AreaPortal>());
+    assert!(layout_size(PRIMITIVE) == size_of::<Primitive>());
+    assert!(layout_size(CUBEMAP) == size_of::<CubemapSample>());
+    assert!(layout_size(OVERLAY) == size_of::<Overlay>());
+    assert!(layout_size(DISP_INFO) == size_of::<DispInfo>());
+};
+
+/// Field layout of the records of the lump `id` in a file of `version`, or `None` if
+/// the crate has no record type for it
+fn record_layout(id: LumpId, version: u32) -> Option<&'static [Run]> {
+    use LumpId::*;
+
+    Some(match id {
+        Planes | TexData | Vertexes | TexInfo | SurfEdges | Models | WorldLights
+        | WorldLightsHdr | Brushes | Areas | DispVerts | PrimVerts | TexDataStringTable => WORDS,
+        Edges | LeafFaces | LeafBrushes | PrimIndices => HALVES,
+        Faces | OriginalFaces | FacesHdr => FACE,
+        Nodes => NODE,
+        Leafs if version <= 19 => LEAF_V0,
+        Leafs => LEAF,
+        BrushSides => BRUSH_SIDE,
+        AreaPortals => AREA_PORTAL,
+        Primitives => PRIMITIVE,
+        Cubemaps => CUBEMAP,
+        Overlays => OVERLAY,
+        DispInfo => DISP_INFO,
+        _ => return None,
+    })
+}
+
+/// Reverse the byte order of every field of every whole record of `data`
+fn swap_records(data: &mut [u8], layout: &[Run]) {
+    for record in data.chunks_exact_mut(layout_size(layout)) {
+        let mut fields = record;
+        for &(width, count) in layout {
+            let (run, rest) = fields.split_at_mut(width * count);
+            for field in run.chunks_exact_mut(width) {
+                field.reverse();
+            }
+            fields = rest;
+        }
+    }
+}
+
+/// Reverse the byte order of the cluster count and offset table of a visibility lump,
+/// leaving the compressed rows, which are plain bytes
+fn swap_visibility(data: &mut [u8]) {
+    let Some(count) = data.first_chunk_mut::<4>() else {
+        return;
+    };
+    count.reverse();
+    let count = usize::try_from(i32::from_le_bytes(*count)).unwrap_or(0);
+    let table_len = count.saturating_mul(2 * 4).min(data.len() - 4);
+    swap_records(&mut data[4..4 + table_len], WORDS);
+}
+
+impl<'a> Bsp<'a> {
+    /// Parse a Source BSP file stored in the given byte order
+    ///
+    /// For big-endian files the header and lump directory are byte-swapped into an owned
+    /// copy, and so is every lump with a record type in this crate, such as
+    /// [`LumpId::Faces`] or [`LumpId::Vertexes`], along with the offset table of
+    /// [`LumpId::Visibility`]. Those read through [`Bsp::lump_cast`] and the typed
+    /// accessors as usual, and are written little-endian.
+    ///
+    /// Every other lump, along with any LZMA-compressed one, is borrowed as-is and stays
+    /// in the file's byte order. That includes [`LumpId::GameLump`] and the physics lumps,
+    /// so a big-endian map written back out still needs those converted to load on PC.
+    pub fn parse_with_endian(data: &'a [u8], endianness: Endianness) -> Result<Self, ParseError> {
+        if endianness == Endianness::Little {
+            return Self::parse(data);
+        }
+
+        let (mut header, _) =
+            Header::read_from_prefix(data).map_err(|_| ParseError::HeaderTooShort)?;
+        // Every header field is 4 bytes wide
+        for word in header.as_mut_bytes().chunks_exact_mut(4) {
+            word.reverse();
+        }
+
+        let ranges = Self::lump_ranges(&header, data.len())?;
+        let data = &data[size_of::<Header>()..];
+        let lumps = core::array::from_fn(|i| {
+            let metadata = header.lump_defs()[i].metadata();
+            // Ranges were checked against the file length above
+            let lump = &data[ranges[i].clone()];
+
+            // Compressed lumps would need decompressing first, which is left to the caller
+            let id = LumpId::try_from(i).ok();
+            let lump = match id {
+                _ if lump.is_empty() || lump.starts_with(b"LZMA") => Cow::Borrowed(lump),
+                Some(LumpId::Visibility) => {
+                    let mut lump = lump.to_vec();
+                    swap_visibility(&mut lump);
+                    Cow::Owned(lump)
+                }
+                Some(id) => match record_layout(id, header.version) {
+                    Some(layout) => {
+                        let mut lump = lump.to_vec();
+                        swap_records(&mut lump, layout);
+                        Cow::Owned(lump)
+                    }
+                    None => Cow::Borrowed(lump),
+                },
+                None => Cow::Borrowed(lump),
+            };
+            LumpCell::new(Cow::Owned(metadata), lump)
+        });
+
+        Ok(Self {
+            header: Cow::Owned(header),
+            lumps,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+    use crate::{BspBuilder, LumpMetadata};
+
+    fn be(fields: &[&[u8]]) -> Vec<u8> {
+        fields.concat()
+    }
+
+    /// Map whose header and lumps are laid out like a console file
+    fn big_endian_file() -> Vec<u8> {
+        let metadata = LumpMetadata {
+            version: 0,
+            identifier: [0; 4],
+        };
+        let vertices = be(&[
+            &1.0f32.to_be_bytes(),
+            &2.0f32.to_be_bytes(),
+            &(-3.5f32).to_be_bytes(),
+        ]);
+        let edges = be(&[&1u16.to_be_bytes(), &2u16.to_be_bytes()]);
+        let face = be(&[
+            &5u16.to_be_bytes(),
+            &[1, 0],
+            &7i32.to_be_bytes(),
+            &4i16.to_be_bytes(),
+            &(-1i16).to_be_bytes(),
+            &(-1i16).to_be_bytes(),
+            &0i16.to_be_bytes(),
+            &[0, 255, 255, 255],
+            &(-1i32).to_be_bytes(),
+            &2.5f32.to_be_bytes(),
+            &1i32.to_be_bytes(),
+            &2i32.to_be_bytes(),
+            &3i32.to_be_bytes(),
+            &4i32.to_be_bytes(),
+            &9i32.to_be_bytes(),
+            &0u16.to_be_bytes(),
+            &6u16.to_be_bytes(),
+            &0x0102_0304u32.to_be_bytes(),
+        ]);
+        let visibility = be(&[
+            &1i32.to_be_bytes(),
+            &12i32.to_be_bytes(),
+            &13i32.to_be_bytes(),
+            &[0b1, 0],
+        ]);
+
+        let mut file = BspBuilder::new(*b"VBSP", 20)
+            .revision(2)
+            .set_lump(LumpId::Entities, metadata, b"{ }\0".to_vec())
+            .set_lump(LumpId::Vertexes, metadata, vertices)
+            .set_lump(LumpId::Edges, metadata, edges)
+            .set_lump(LumpId::Faces, metadata, face)
+            .set_lump(LumpId::Visibility, metadata, visibility)
+            .build()
+            .write_to_vec();
+        for word in file[..size_of::<Header>()].chunks_exact_mut(4) {
+            word.reverse();
+        }
+        file
+    }
+
+    #[test]
+    fn big_endian_records() {
+        let file = big_endian_file();
+        assert_eq!(Endianness::detect(&file), Some(Endianness::Big));
+        let bsp = Bsp::parse_with_endian(&file, Endianness::Big).unwrap();
+        assert_eq!(bsp.map_revision(), 2);
+
+        let vertex = bsp.vertices().unwrap()[0];
+        assert_eq!([vertex.x, vertex.y, vertex.z], [1.0, 2.0, -3.5]);
+        assert_eq!(*bsp.edges().unwrap(), [[1, 2]]);
+
+        let face = bsp.faces().unwrap()[0];
+        assert_eq!((face.planenum, face.side, face.on_node), (5, 1, 0));
+        assert_eq!((face.firstedge, face.numedges, face.texinfo), (7, 4, -1));
+        assert_eq!(face.styles, [0, 255, 255, 255]);
+        assert_eq!((face.lightofs, face.area), (-1, 2.5));
+        assert_eq!((face.lightmap_mins, face.lightmap_size), ([1, 2], [3, 4]));
+        assert_eq!(
+            (face.orig_face, face.num_prims, face.first_prim_id),
+            (9, 0, 6)
+        );
+        assert_eq!(face.smoothing_groups, 0x0102_0304);
+
+        let vis = bsp.visibility().unwrap();
+        assert_eq!(vis.num_clusters(), 1);
+        assert!(vis.can_see(0, 0));
+        assert!(!vis.can_hear(0, 0));
+
+        assert_eq!(bsp.entities().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn written_records_are_little_endian() {
+        let file = big_endian_file();
+        let bsp = Bsp::parse_with_endian(&file, Endianness::Big).unwrap();
+        let written = bsp.write_to_vec();
+        assert_eq!(Endianness::detect(&written), Some(Endianness::Little));
+
+        let reparsed = Bsp::parse(&written).unwrap();
+        assert_eq!(*reparsed.edges().unwrap(), [[1, 2]]);
+        assert_eq!(reparsed.faces().unwrap()[0].smoothing_groups, 0x0102_0304);
+    }
+}
@@ -0,0 +1,396 @@
+use alloc::{borrow::Cow, string::String, vec::Vec};
+use core::ops::Deref;
+
+use crate::{Bsp, LumpId};
+
+/// Map entity, as an ordered list of key-value pairs
+///
+/// Duplicate keys are kept in the order they appear in the lump.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Entity(pub Vec<(String, String)>);
+
+impl Entity {
+    /// Value of the first pair with the given key
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Set the value of the first pair with the given key, appending a pair if there is
+    /// none
+    pub fn set(&mut self, key: &str, value: &str) {
+        match self.0.iter_mut().find(|(k, _)| k == key) {
+            Some((_, v)) => *v = value.into(),
+            None => self.0.push((key.into(), value.into())),
+        }
+    }
+
+    /// Value of the `classname` key, such as `worldspawn` or `info_player_start`
+    pub fn classname(&self) -> Option<&str> {
+        self.get("classname")
+    }
+
+    /// Value of `key` parsed as three space-separated numbers, such as `origin`
+    ///
+    /// Returns `None` if the key is missing or doesn't hold exactly three numbers.
+    pub fn get_vec3(&self, key: &str) -> Option<[f32; 3]> {
+        let mut components = self.get(key)?.split_ascii_whitespace();
+        let mut vec = [0.0; 3];
+        for c in &mut vec {
+            *c = components.next()?.parse().ok()?;
+        }
+        components.next().is_none().then_some(vec)
+    }
+
+    /// Value of `key` parsed as a float, ignoring surrounding whitespace
+    pub fn get_f32(&self, key: &str) -> Option<f32> {
+        self.get(key)?.trim_ascii().parse().ok()
+    }
+
+    /// Value of `key` parsed as an integer, ignoring surrounding whitespace
+    pub fn get_i32(&self, key: &str) -> Option<i32> {
+        self.get(key)?.trim_ascii().parse().ok()
+    }
+
+    /// Value of `key` parsed as a `0` or `1` flag, ignoring surrounding whitespace
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        match self.get(key)?.trim_ascii() {
+            "0" => Some(false),
+            "1" => Some(true),
+            _ => None,
+        }
+    }
+}
+
+/// Error returned when parsing the entity lump
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntityParseError {
+    /// Unexpected byte where a brace or quoted string was expected
+    UnexpectedByte {
+        /// Offset into the entity lump
+        offset: usize,
+        /// Byte found at `offset`
+        byte: u8,
+    },
+    /// Quoted string is missing its closing quote
+    UnterminatedString {
+        /// Offset of the opening quote
+        offset: usize,
+    },
+    /// Lump ended before the closing brace of an entity
+    UnterminatedEntity {
+        /// Offset of the opening brace
+        offset: usize,
+    },
+    /// Key is missing its value
+    MissingValue {
+        /// Offset of the key
+        offset: usize,
+    },
+}
+
+impl core::fmt::Display for EntityParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnexpectedByte { offset, byte } => {
+                write!(f, "unexpected byte {byte:#04x} at offset {offset}")
+            }
+            Self::UnterminatedString { offset } => {
+                write!(f, "unterminated string starting at offset {offset}")
+            }
+            Self::UnterminatedEntity { offset } => {
+                write!(f, "unterminated entity starting at offset {offset}")
+            }
+            Self::MissingValue { offset } => {
+                write!(f, "key at offset {offset} is missing a value")
+            }
+        }
+    }
+}
+
+impl core::error::Error for EntityParseError {}
+
+/// Incremental parser over entity lump text, yielding one [`Entity`] per `{ ... }` block
+///
+/// Stops after the first error. `D` is anything holding the text, such as the [`Ref`]
+/// guarding the lump.
+///
+/// [`Ref`]: core::cell::Ref
+pub(crate) struct EntityParser<D> {
+    data: D,
+    pos: usize,
+}
+
+impl<D> EntityParser<D>
+where
+    D: Deref<Target = [u8]>,
+{
+    pub(crate) fn new(data: D) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    /// Skip whitespace and null bytes
+    fn skip_blank(&mut self) {
+        while let Some(b) = self.data.get(self.pos) {
+            if !b.is_ascii_whitespace() && *b != 0 {
+                break;
+            }
+            self.pos += 1;
+        }
+    }
+
+    /// Parse a quoted string starting at the current position
+    fn string(&mut self) -> Result<String, EntityParseError> {
+        let start = self.pos;
+        let rest = &self.data[start + 1..];
+        let len = rest
+            .iter()
+            .position(|&b| b == b'"')
+            .ok_or(EntityParseError::UnterminatedString { offset: start })?;
+
+        self.pos = start + 1 + len + 1;
+        Ok(String::from_utf8_lossy(&rest[..len]).into_owned())
+    }
+
+    fn entity(&mut self) -> Result<Entity, EntityParseError> {
+        let start = self.pos;
+        match self.data[start] {
+            b'{' => self.pos += 1,
+            byte => {
+                return Err(EntityParseError::UnexpectedByte {
+                    offset: start,
+                    byte,
+                })
+            }
+        }
+
+        let mut pairs = Vec::new();
+        loop {
+            self.skip_blank();
+            let key_offset = self.pos;
+            let key = match self.data.get(key_offset) {
+                None => return Err(EntityParseError::UnterminatedEntity { offset: start }),
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(Entity(pairs));
+                }
+                Some(b'"') => self.string()?,
+                Some(&byte) => {
+                    return Err(EntityParseError::UnexpectedByte {
+                        offset: key_offset,
+                        byte,
+                    })
+                }
+            };
+
+            self.skip_blank();
+            let value = match self.data.get(self.pos) {
+                Some(b'"') => self.string()?,
+                _ => return Err(EntityParseError::MissingValue { offset: key_offset }),
+            };
+            pairs.push((key, value));
+        }
+    }
+}
+
+impl<D> Iterator for EntityParser<D>
+where
+    D: Deref<Target = [u8]>,
+{
+    type Item = Result<Entity, EntityParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.skip_blank();
+        if self.pos >= self.data.len() {
+            return None;
+        }
+
+        let entity = self.entity();
+        if entity.is_err() {
+            // Don't attempt to resynchronize after malformed input
+            self.pos = self.data.len();
+        }
+        Some(entity)
+    }
+}
+
+impl Bsp<'_> {
+    /// Parse [`LumpId::Entities`] into a list of [`Entity`]
+    ///
+    /// The lump is expected to be a sequence of `{ "key" "value" ... }` blocks without
+    /// nesting or comments. Trailing whitespace and null bytes are ignored, and invalid
+    /// UTF-8 is replaced lossily.
+    pub fn entities(&self) -> Result<Vec<Entity>, EntityParseError> {
+        self.entities_iter().collect()
+    }
+
+    /// Parse [`LumpId::Entities`] lazily, one [`Entity`] per `{ ... }` block
+    ///
+    /// Blocks are parsed like [`Bsp::entities`] as the iterator advances, so finding one
+    /// entity doesn't parse the ones after it. The iterator ends after the first error.
+    /// It keeps the lump borrowed until dropped, so editing the lump meanwhile panics.
+    pub fn entities_iter(&self) -> impl Iterator<Item = Result<Entity, EntityParseError>> + '_ {
+        EntityParser::new(self.raw(LumpId::Entities))
+    }
+
+    /// Parse the entities whose classname is `class` lazily, like [`Bsp::entities_iter`]
+    ///
+    /// Parse errors are passed through, so the iterator still ends after the first one.
+    pub fn entities_by_classname<'s>(
+        &'s self,
+        class: &'s str,
+    ) -> impl Iterator<Item = Result<Entity, EntityParseError>> + 's {
+        self.entities_iter().filter(move |entity| match entity {
+            Ok(entity) => entity.classname() == Some(class),
+            Err(_) => true,
+        })
+    }
+
+    /// The `worldspawn` entity, which the compiler always writes first
+    ///
+    /// Only the first entity is parsed. Returns `None` if it fails to parse or isn't
+    /// `worldspawn`.
+    pub fn worldspawn(&self) -> Option<Entity> {
+        self.entities_iter()
+            .next()?
+            .ok()
+            .filter(|entity| entity.classname() == Some("worldspawn"))
+    }
+
+    /// Serialize entities into [`LumpId::Entities`], replacing its contents
+    ///
+    /// Entities are written in the form emitted by Valve's compiler, one pair per line
+    /// inside braces, followed by a terminating null byte. Key order is preserved. The
+    /// classic format has no escape sequences, so keys and values containing `"` can't
+    /// be represented and will not survive a round-trip through [`Bsp::entities`].
+    pub fn set_entities(&self, entities: &[Entity]) {
+        let mut data = Vec::new();
+        for entity in entities {
+            data.extend_from_slice(b"{\n");
+            for (key, value) in &entity.0 {
+                data.push(b'"');
+                data.extend_from_slice(key.as_bytes());
+                data.extend_from_slice(b"\" \"");
+                data.extend_from_slice(value.as_bytes());
+                data.extend_from_slice(b"\"\n");
+            }
+            data.extend_from_slice(b"}\n");
+        }
+        data.push(0);
+
+        let (_, mut lump) = self.lump_mut(LumpId::Entities);
+        *lump = Cow::Owned(data);
+    }
+
+    /// Parse [`LumpId::Entities`], let `f` edit the entities and write them back
+    ///
+    /// The edited list is serialized like [`Bsp::set_entities`], so the lump becomes
+    /// owned and dirty even if `f` changes nothing. On a parse error `f` isn't called
+    /// and the lump is left untouched.
+    pub fn edit_entities<F>(&self, f: F) -> Result<(), EntityParseError>
+    where
+        F: FnOnce(&mut Vec<Entity>),
+    {
+        let mut entities = self.entities()?;
+        f(&mut entities);
+        self.set_entities(&entities);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use zerocopy::FromZeros;
+
+    use super::*;
+    use crate::{BspBuilder, LumpMetadata};
+
+    fn with_entities(text: &[u8]) -> Bsp<'static> {
+        BspBuilder::new(*b"VBSP", 20)
+            .set_lump(LumpId::Entities, LumpMetadata::new_zeroed(), text.to_vec())
+            .build()
+    }
+
+    fn entity(pairs: &[(&str, &str)]) -> Entity {
+        Entity(pairs.iter().map(|&(k, v)| (k.into(), v.into())).collect())
+    }
+
+    #[test]
+    fn empty_lump() {
+        assert_eq!(with_entities(b"").entities(), Ok(Vec::new()));
+        assert_eq!(with_entities(b"\0").entities(), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn trailing_nulls_and_whitespace() {
+        let bsp = with_entities(b"{\n\"classname\" \"worldspawn\"\n}\n \t\r\n\0\0\0");
+        assert_eq!(
+            bsp.entities(),
+            Ok(vec![entity(&[("classname", "worldspawn")])])
+        );
+    }
+
+    #[test]
+    fn missing_final_brace() {
+        let bsp =
+            with_entities(b"{\n\"classname\" \"worldspawn\"\n}\n{\n\"classname\" \"light\"\n\0");
+        assert_eq!(
+            bsp.entities(),
+            Err(EntityParseError::UnterminatedEntity { offset: 29 })
+        );
+
+        // Entities before the unterminated one are still yielded lazily
+        let mut iter = bsp.entities_iter();
+        assert_eq!(
+            iter.next(),
+            Some(Ok(entity(&[("classname", "worldspawn")])))
+        );
+        assert!(iter.next().unwrap().is_err());
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn duplicate_keys_are_kept() {
+        let bsp = with_entities(b"{ \"OnTrigger\" \"a\" \"OnTrigger\" \"b\" }");
+        let entities = bsp.entities().unwrap();
+        assert_eq!(
+            entities,
+            vec![entity(&[("OnTrigger", "a"), ("OnTrigger", "b")])]
+        );
+        assert_eq!(entities[0].get("OnTrigger"), Some("a"));
+    }
+
+    #[test]
+    fn values_with_spaces() {
+        let bsp = with_entities(b"{\"origin\" \"1 -2.5 3\" \"targetname\" \"door one \"}");
+        let entities = bsp.entities().unwrap();
+        assert_eq!(entities[0].get("origin"), Some("1 -2.5 3"));
+        assert_eq!(entities[0].get("targetname"), Some("door one "));
+        assert_eq!(entities[0].get_vec3("origin"), Some([1.0, -2.5, 3.0]));
+    }
+
+    #[test]
+    fn set_and_edit_round_trip() {
+        let bsp = with_entities(b"");
+        let entities = vec![
+            entity(&[("classname", "worldspawn"), ("message", "a map")]),
+            entity(&[("classname", "light"), ("_light", "255 255 255 200")]),
+        ];
+        bsp.set_entities(&entities);
+        assert_eq!(bsp.entities(), Ok(entities.clone()));
+
+        bsp.edit_entities(|entities| {
+            entities[1].set("_light", "0 0 0 0");
+            entities.push(entity(&[("classname", "info_player_start")]));
+        })
+        .unwrap();
+        let edited = bsp.entities().unwrap();
+        assert_eq!(edited.len(), 3);
+        assert_eq!(edited[0], entities[0]);
+        assert_eq!(edited[1].get("_light"), Some("0 0 0 0"));
+        assert_eq!(edited[2].classname(), Some("info_player_start"));
+    }
+}
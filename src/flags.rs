@@ -0,0 +1,158 @@
+use bitflags::bitflags;
+
+use crate::{Brush, DispInfo, Leaf, LeafV0, TexInfo};
+
+bitflags! {
+    /// Contents flags of a brush or leaf (`CONTENTS_*`)
+    ///
+    /// Bits without a named flag are kept, since games add their own.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize))]
+    pub struct Contents: u32 {
+        /// Solid
+        const SOLID = 0x1;
+        /// Translucent, but not watery
+        const WINDOW = 0x2;
+        /// Unused
+        const AUX = 0x4;
+        /// Lets bullets through but blocks movement
+        const GRATE = 0x8;
+        /// Slime
+        const SLIME = 0x10;
+        /// Water
+        const WATER = 0x20;
+        /// Blocks line of sight only
+        const BLOCKLOS = 0x40;
+        /// Blocks visibility
+        const OPAQUE = 0x80;
+        /// Fog volume test
+        const TESTFOGVOLUME = 0x100;
+        /// Unused
+        const UNUSED = 0x200;
+        /// Blocks light
+        const BLOCKLIGHT = 0x400;
+        /// Team 1 collision
+        const TEAM1 = 0x800;
+        /// Team 2 collision
+        const TEAM2 = 0x1000;
+        /// Ignores opacity from `nodraw` surfaces
+        const IGNORE_NODRAW_OPAQUE = 0x2000;
+        /// Moves with its entity
+        const MOVEABLE = 0x4000;
+        /// Area portal
+        const AREAPORTAL = 0x8000;
+        /// Blocks players
+        const PLAYERCLIP = 0x10000;
+        /// Blocks NPCs
+        const MONSTERCLIP = 0x20000;
+        /// Current flowing towards 0 degrees
+        const CURRENT_0 = 0x40000;
+        /// Current flowing towards 90 degrees
+        const CURRENT_90 = 0x80000;
+        /// Current flowing towards 180 degrees
+        const CURRENT_180 = 0x100000;
+        /// Current flowing towards 270 degrees
+        const CURRENT_270 = 0x200000;
+        /// Current flowing up
+        const CURRENT_UP = 0x400000;
+        /// Current flowing down
+        const CURRENT_DOWN = 0x800000;
+        /// Origin brush, removed by the compiler
+        const ORIGIN = 0x1000000;
+        /// NPC, never stored in a map
+        const MONSTER = 0x2000000;
+        /// Debris
+        const DEBRIS = 0x4000000;
+        /// Detail brush, which doesn't split the BSP tree
+        const DETAIL = 0x8000000;
+        /// Translucent surfaces
+        const TRANSLUCENT = 0x10000000;
+        /// Ladder
+        const LADDER = 0x20000000;
+        /// Hitbox trace, never stored in a map
+        const HITBOX = 0x40000000;
+
+        // Game-specific bits
+        const _ = !0;
+    }
+}
+
+bitflags! {
+    /// Surface flags of a texture projection (`SURF_*`)
+    ///
+    /// Bits without a named flag are kept, since games add their own.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize))]
+    pub struct SurfaceFlags: u32 {
+        /// Emits light
+        const LIGHT = 0x1;
+        /// 2D skybox
+        const SKY2D = 0x2;
+        /// 3D skybox
+        const SKY = 0x4;
+        /// Turbulent water warp
+        const WARP = 0x8;
+        /// Translucent
+        const TRANS = 0x10;
+        /// Can't have portals placed on it
+        const NOPORTAL = 0x20;
+        /// Trigger surface, only used by the Xbox compiler
+        const TRIGGER = 0x40;
+        /// Not rendered
+        const NODRAW = 0x80;
+        /// Hint brush face, which splits the BSP tree
+        const HINT = 0x100;
+        /// Ignored face of a hint brush
+        const SKIP = 0x200;
+        /// Gets no lightmap
+        const NOLIGHT = 0x400;
+        /// Has a bumped lightmap, see [`Lightmap::bumped`](crate::Lightmap::bumped)
+        const BUMPLIGHT = 0x800;
+        /// Doesn't receive shadows
+        const NOSHADOWS = 0x1000;
+        /// Doesn't receive decals
+        const NODECALS = 0x2000;
+        /// Not subdivided to fit lightmap size limits
+        const NOCHOP = 0x4000;
+        /// Part of a hitbox
+        const HITBOX = 0x8000;
+
+        // Game-specific bits
+        const _ = !0;
+    }
+}
+
+impl Brush {
+    /// Contents flags
+    pub fn contents(&self) -> Contents {
+        Contents::from_bits_retain(self.contents as u32)
+    }
+}
+
+impl Leaf {
+    /// Contents flags
+    pub fn contents(&self) -> Contents {
+        Contents::from_bits_retain(self.contents as u32)
+    }
+}
+
+impl LeafV0 {
+    /// Contents flags
+    pub fn contents(&self) -> Contents {
+        Contents::from_bits_retain(self.contents as u32)
+    }
+}
+
+impl DispInfo {
+    /// Contents flags
+    pub fn contents(&self) -> Contents {
+        Contents::from_bits_retain(self.contents as u32)
+    }
+}
+
+impl TexInfo {
+    /// Surface flags
+    pub fn surface_flags(&self) -> SurfaceFlags {
+        SurfaceFlags::from_bits_retain(self.flags as u32)
+    }
+}
@@ -0,0 +1,106 @@
+use alloc::boxed::Box;
+
+use crate::{Bsp, BspFile, BspHeader, ParseError, QuakeBsp, RespawnBsp};
+
+/// BSP format family, as identified by the file magic
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BspFlavor {
+    /// Valve Source engine (`VBSP`)
+    Source,
+    /// id Tech 2/3 (`IBSP`)
+    Id,
+    /// Respawn Titanfall/Apex (`rBSP`)
+    Respawn,
+    /// Quake (version 29, no magic)
+    Quake,
+    /// GoldSrc (version 30, no magic)
+    GoldSrc,
+    /// Unrecognized file magic
+    Unknown([u8; 4]),
+}
+
+impl BspFlavor {
+    /// Identify the flavor from a file magic
+    pub fn from_magic(magic: [u8; 4]) -> Self {
+        match &magic {
+            b"VBSP" => Self::Source,
+            b"IBSP" => Self::Id,
+            b"rBSP" => Self::Respawn,
+            // Quake and GoldSrc files start with their version instead of a magic
+            [29, 0, 0, 0] => Self::Quake,
+            [30, 0, 0, 0] => Self::GoldSrc,
+            _ => Self::Unknown(magic),
+        }
+    }
+
+    /// Identify the flavor from the first four bytes of a file, or `None` if it's too short
+    pub fn detect(data: &[u8]) -> Option<Self> {
+        Some(Self::from_magic(*data.first_chunk()?))
+    }
+
+    /// Whether the magic was recognized
+    pub fn is_known(&self) -> bool {
+        !matches!(self, Self::Unknown(_))
+    }
+}
+
+impl<'a, H, const N: usize> BspFile<'a, H, N>
+where
+    H: BspHeader<N>,
+{
+    /// Parse like [`BspFile::parse`], but reject files whose magic doesn't match the
+    /// [`BspFlavor`] of the header layout
+    pub fn parse_strict(data: &'a [u8]) -> Result<Self, ParseError> {
+        let bsp = Self::parse(data)?;
+        match bsp.flavor() {
+            flavor if H::FLAVORS.contains(&flavor) => Ok(bsp),
+            _ => Err(ParseError::BadMagic(bsp.header.magic())),
+        }
+    }
+
+    /// Flavor detected from the file magic
+    pub fn flavor(&self) -> BspFlavor {
+        BspFlavor::from_magic(self.header.magic())
+    }
+}
+
+/// BSP file of any supported format, detected from the file magic
+///
+/// Each variant is boxed since the lump arrays differ greatly in size.
+#[derive(Debug)]
+pub enum AnyBsp<'a> {
+    /// Source engine (`VBSP`)
+    Source(Box<Bsp<'a>>),
+    /// Respawn (`rBSP`)
+    Respawn(Box<RespawnBsp<'a>>),
+    /// Quake or GoldSrc
+    Quake(Box<QuakeBsp<'a>>),
+}
+
+impl<'a> AnyBsp<'a> {
+    /// Detect the format of `data` and parse it with the matching header layout
+    ///
+    /// Files whose magic isn't a supported [`BspFlavor`] are rejected with
+    /// [`ParseError::BadMagic`].
+    pub fn parse(data: &'a [u8]) -> Result<Self, ParseError> {
+        match BspFlavor::detect(data).ok_or(ParseError::HeaderTooShort)? {
+            BspFlavor::Source => Bsp::parse(data).map(|bsp| Self::Source(Box::new(bsp))),
+            BspFlavor::Respawn => RespawnBsp::parse(data).map(|bsp| Self::Respawn(Box::new(bsp))),
+            BspFlavor::Quake | BspFlavor::GoldSrc => {
+                QuakeBsp::parse(data).map(|bsp| Self::Quake(Box::new(bsp)))
+            }
+            BspFlavor::Id | BspFlavor::Unknown(_) => {
+                Err(ParseError::BadMagic(*data.first_chunk().unwrap_or(&[0; 4])))
+            }
+        }
+    }
+
+    /// Flavor detected from the file magic
+    pub fn flavor(&self) -> BspFlavor {
+        match self {
+            Self::Source(bsp) => bsp.flavor(),
+            Self::Respawn(bsp) => bsp.flavor(),
+            Self::Quake(bsp) => bsp.flavor(),
+        }
+    }
+}
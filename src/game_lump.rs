@@ -0,0 +1,266 @@
+use alloc::{string::String, vec::Vec};
+use core::cell::Ref;
+
+use zerocopy::{FromBytes, IntoBytes};
+use zerocopy_derive::*;
+
+use crate::{Bsp, LumpId};
+
+mod static_props;
+
+pub use static_props::{StaticProp, StaticProps, StaticPropsError, STATIC_PROPS_ID};
+
+/// Game lump directory entry as stored in [`LumpId::GameLump`]
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Debug, Clone, Copy)]
+#[repr(C)]
+struct RawGameLumpDef {
+    id: u32,
+    flags: u16,
+    version: u16,
+    offset: i32,
+    length: i32,
+}
+
+/// Game lump definition
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameLumpDef {
+    /// Game lump identifier, in reading order (e.g. `*b"sprp"`)
+    pub id: [u8; 4],
+    /// Game lump flags
+    pub flags: u16,
+    /// Game lump version
+    pub version: u16,
+    /// Absolute offset in file
+    pub offset: i32,
+    /// Length of data
+    pub length: i32,
+}
+
+impl GameLumpDef {
+    /// Identifier as text, if it's valid UTF-8
+    pub fn fourcc(&self) -> Option<&str> {
+        core::str::from_utf8(&self.id).ok()
+    }
+}
+
+impl From<RawGameLumpDef> for GameLumpDef {
+    fn from(raw: RawGameLumpDef) -> Self {
+        Self {
+            // The identifier is a multi-character literal stored as a little-endian
+            // integer, so its bytes appear reversed on disk
+            id: raw.id.to_be_bytes(),
+            flags: raw.flags,
+            version: raw.version,
+            offset: raw.offset,
+            length: raw.length,
+        }
+    }
+}
+
+/// Error returned when reading the game lump
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameLumpError {
+    /// Game lump is too short to contain its directory
+    Truncated,
+    /// Game lump data lies outside of [`LumpId::GameLump`]
+    OutOfBounds {
+        /// Game lump identifier
+        id: [u8; 4],
+    },
+}
+
+impl core::fmt::Display for GameLumpError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Truncated => f.write_str("game lump directory is truncated"),
+            Self::OutOfBounds { id } => write!(
+                f,
+                "game lump {:?} lies outside of the game lump",
+                String::from_utf8_lossy(id)
+            ),
+        }
+    }
+}
+
+impl core::error::Error for GameLumpError {}
+
+/// Shift the offset of every entry in the game lump directory at the start of `data` by
+/// `delta` bytes, after the game lump moved that far in the file
+///
+/// Data too short to hold its directory is left untouched.
+pub(crate) fn shift_offsets(data: &mut [u8], delta: i32) {
+    if delta == 0 {
+        return;
+    }
+    let Ok((count, _)) = i32::read_from_prefix(data) else {
+        return;
+    };
+    let Some(defs) = usize::try_from(count)
+        .ok()
+        .and_then(|count| count.checked_mul(size_of::<RawGameLumpDef>()))
+        .and_then(|len| data.get_mut(size_of::<i32>()..)?.get_mut(..len))
+    else {
+        return;
+    };
+
+    for chunk in defs.chunks_exact_mut(size_of::<RawGameLumpDef>()) {
+        if let Ok(mut raw) = RawGameLumpDef::read_from_bytes(chunk) {
+            raw.offset = raw.offset.wrapping_add(delta);
+            raw.write_to(chunk).expect("chunk is one entry long");
+        }
+    }
+}
+
+impl Bsp<'_> {
+    /// Parse the directory of [`LumpId::GameLump`]
+    pub fn game_lumps(&self) -> Result<Vec<GameLumpDef>, GameLumpError> {
+        let (_, data) = self.lump(LumpId::GameLump);
+        if data.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let (count, rest) = i32::read_from_prefix(&data).map_err(|_| GameLumpError::Truncated)?;
+        let count = usize::try_from(count).map_err(|_| GameLumpError::Truncated)?;
+        let defs = count
+            .checked_mul(size_of::<RawGameLumpDef>())
+            .and_then(|len| rest.get(..len))
+            .ok_or(GameLumpError::Truncated)?;
+
+        // Entries are copied out rather than cast in place since the lump isn't
+        // guaranteed to be aligned
+        Ok(defs
+            .chunks_exact(size_of::<RawGameLumpDef>())
+            .filter_map(|chunk| RawGameLumpDef::read_from_bytes(chunk).ok())
+            .map(GameLumpDef::from)
+            .collect())
+    }
+
+    /// Data of a game lump described by `def`
+    ///
+    /// Game lump offsets are absolute offsets into the file, so they're resolved against
+    /// the position [`LumpId::GameLump`] had in the header this [`Bsp`] was parsed from.
+    /// Every writer shifts them by however far the game lump moves.
+    pub fn game_lump_data(&self, def: &GameLumpDef) -> Result<Ref<'_, [u8]>, GameLumpError> {
+        let base = self.header.lump_defs[LumpId::GameLump as usize].offset as usize;
+        let (_, data) = self.lump(LumpId::GameLump);
+
+        let range = usize::try_from(def.offset)
+            .ok()
+            .and_then(|offset| offset.checked_sub(base))
+            .zip(usize::try_from(def.length).ok())
+            .and_then(|(start, length)| Some(start..start.checked_add(length)?))
+            .filter(|range| range.end <= data.len())
+            .ok_or(GameLumpError::OutOfBounds { id: def.id })?;
+
+        Ok(Ref::map(data, |v| &v[range]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use zerocopy::FromZeros;
+
+    use super::*;
+    use crate::{BspBuilder, LumpMetadata};
+
+    /// Game lump with a `sprp` and a `dprp` entry, offsets relative to the start of the lump
+    fn game_lump() -> Vec<u8> {
+        let header_len = (size_of::<i32>() + 2 * size_of::<RawGameLumpDef>()) as i32;
+        let mut data = 2i32.as_bytes().to_vec();
+        for (id, offset, length) in [(*b"sprp", header_len, 6), (*b"dprp", header_len + 6, 3)] {
+            let raw = RawGameLumpDef {
+                id: u32::from_be_bytes(id),
+                flags: 0,
+                version: 4,
+                offset,
+                length,
+            };
+            data.extend_from_slice(raw.as_bytes());
+        }
+        data.extend_from_slice(b"staticdet");
+        data
+    }
+
+    /// Identifier and data of every game lump
+    fn contents(bsp: &Bsp<'_>) -> Vec<([u8; 4], Vec<u8>)> {
+        bsp.game_lumps()
+            .unwrap()
+            .iter()
+            .map(|def| (def.id, bsp.game_lump_data(def).unwrap().to_vec()))
+            .collect()
+    }
+
+    fn built() -> Bsp<'static> {
+        BspBuilder::new(*b"VBSP", 20)
+            .set_lump(LumpId::Entities, LumpMetadata::new_zeroed(), vec![1; 5])
+            .set_lump(LumpId::GameLump, LumpMetadata::new_zeroed(), game_lump())
+            .build()
+    }
+
+    #[test]
+    fn builder_resolves_relative_offsets() {
+        let expected = vec![(*b"sprp", b"static".to_vec()), (*b"dprp", b"det".to_vec())];
+        assert_eq!(contents(&built()), expected);
+    }
+
+    #[test]
+    fn writers_follow_moved_game_lump() {
+        let bsp = built();
+        let expected = contents(&bsp);
+
+        let file = bsp.write_to_vec();
+        let reparsed = Bsp::parse(&file).unwrap();
+        assert_eq!(contents(&reparsed), expected);
+
+        let mut buf = vec![0; file.len()];
+        let len = bsp.write_to_slice(&mut buf).unwrap();
+        assert_eq!(buf[..len], file);
+
+        #[cfg(feature = "std")]
+        for align in [1, 16, 64] {
+            let mut file = Vec::new();
+            reparsed.write_to_io_aligned(&mut file, align).unwrap();
+            let moved = Bsp::parse(&file).unwrap();
+            assert_ne!(
+                moved.original_lump_defs()[LumpId::GameLump as usize].offset,
+                reparsed.original_lump_defs()[LumpId::GameLump as usize].offset,
+                "align {align}"
+            );
+            assert_eq!(contents(&moved), expected, "align {align}");
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn preserving_writer_follows_moved_game_lump() {
+        let file = built().write_to_vec();
+        let mut bsp = Bsp::parse(&file).unwrap();
+        let expected = contents(&bsp);
+
+        // Dirtying the game lump moves it after every untouched lump
+        drop(bsp.lump_mut(LumpId::GameLump));
+        let mut preserved = Vec::new();
+        bsp.write_to_io_preserving(&mut preserved).unwrap();
+        assert_eq!(contents(&Bsp::parse(&preserved).unwrap()), expected);
+
+        bsp.lump_mut(LumpId::Entities).1.to_mut().extend([2; 300]);
+        bsp.repack();
+        let mut repacked = Vec::new();
+        bsp.write_to_io_preserving(&mut repacked).unwrap();
+        assert_eq!(contents(&Bsp::parse(&repacked).unwrap()), expected);
+    }
+
+    #[cfg(feature = "lzma")]
+    #[test]
+    fn compressed_writer_follows_moved_game_lump() {
+        let bsp = built();
+        let expected = contents(&bsp);
+
+        let mut file = Vec::new();
+        bsp.write_to_io_compressed(&mut file, crate::CompressionPolicy::All)
+            .unwrap();
+        assert_eq!(contents(&Bsp::parse(&file).unwrap()), expected);
+    }
+}
@@ -0,0 +1,308 @@
+use alloc::{string::String, vec::Vec};
+
+use zerocopy::FromBytes;
+
+use crate::{Bsp, GameLumpError};
+
+/// Game lump identifier of static props
+pub const STATIC_PROPS_ID: [u8; 4] = *b"sprp";
+
+/// Length of a model name in the static prop dictionary
+const MODEL_NAME_LEN: usize = 128;
+
+/// Static prop (`StaticPropLump_t`)
+///
+/// The on-disk layout grows with the game lump version, so fields introduced after
+/// version 4 are `None` when the map predates them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StaticProp {
+    /// Origin
+    pub origin: [f32; 3],
+    /// Orientation (pitch, yaw, roll)
+    pub angles: [f32; 3],
+    /// Index into [`StaticProps::names`]
+    pub prop_type: u16,
+    /// Index into [`StaticProps::leaves`]
+    pub first_leaf: u16,
+    /// Number of leaves the prop occupies
+    pub leaf_count: u16,
+    /// Solidity type
+    pub solid: u8,
+    /// Prop flags
+    pub flags: u8,
+    /// Model skin
+    pub skin: i32,
+    /// Distance at which the prop starts fading
+    pub fade_min_dist: f32,
+    /// Distance at which the prop is fully faded
+    pub fade_max_dist: f32,
+    /// Origin used for lighting
+    pub lighting_origin: [f32; 3],
+    /// Fade distance scale (v5+)
+    pub forced_fade_scale: Option<f32>,
+    /// Minimum and maximum DirectX level the prop is visible at (v6 and v7)
+    pub dx_level: Option<[u16; 2]>,
+    /// Minimum and maximum CPU level the prop is visible at (v8+)
+    pub cpu_level: Option<[u8; 2]>,
+    /// Minimum and maximum GPU level the prop is visible at (v8+)
+    pub gpu_level: Option<[u8; 2]>,
+    /// Per-instance color and alpha modulation (v7+)
+    pub diffuse_modulation: Option<[u8; 4]>,
+    /// Whether the prop is hidden on Xbox 360 (v9+)
+    pub disable_x360: Option<bool>,
+    /// Extended flags (v10+)
+    pub flags_ex: Option<u32>,
+    /// Uniform scale (v11+)
+    pub uniform_scale: Option<f32>,
+}
+
+/// Decoded static prop game lump
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StaticProps {
+    /// Game lump version the props were decoded from
+    pub version: u16,
+    /// Model name dictionary
+    pub names: Vec<String>,
+    /// Leaf indices referenced by [`StaticProp::first_leaf`], see
+    /// [`StaticProps::prop_leaves`]
+    pub leaves: Vec<u16>,
+    /// Props
+    pub props: Vec<StaticProp>,
+}
+
+impl StaticProps {
+    /// Model name of a prop, resolved through the dictionary
+    pub fn model_name(&self, prop: &StaticProp) -> Option<&str> {
+        self.names.get(prop.prop_type as usize).map(String::as_str)
+    }
+
+    /// Leaf array, which follows the dictionary in the game lump
+    pub fn leaf_entries(&self) -> &[u16] {
+        &self.leaves
+    }
+
+    /// Leaves a prop occupies, resolved through [`StaticProp::first_leaf`] and
+    /// [`StaticProp::leaf_count`]
+    ///
+    /// Returns `None` if the range runs past the end of the leaf array.
+    pub fn prop_leaves(&self, prop: &StaticProp) -> Option<&[u16]> {
+        let first = usize::from(prop.first_leaf);
+        self.leaves.get(first..first + usize::from(prop.leaf_count))
+    }
+}
+
+/// Error returned when decoding static props
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StaticPropsError {
+    /// Game lump directory couldn't be read
+    GameLump(GameLumpError),
+    /// Map has no static prop game lump
+    Missing,
+    /// Static prop game lump is compressed and the `lzma` feature is disabled, or the
+    /// compressed data is corrupt
+    Compressed,
+    /// Static prop game lump version isn't supported
+    UnsupportedVersion(u16),
+    /// Static prop game lump ended early
+    Truncated,
+}
+
+impl core::fmt::Display for StaticPropsError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::GameLump(e) => e.fmt(f),
+            Self::Missing => f.write_str("map has no static prop game lump"),
+            Self::Compressed => f.write_str("static prop game lump could not be decompressed"),
+            Self::UnsupportedVersion(v) => write!(f, "unsupported static prop version {v}"),
+            Self::Truncated => f.write_str("static prop game lump is truncated"),
+        }
+    }
+}
+
+impl core::error::Error for StaticPropsError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::GameLump(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<GameLumpError> for StaticPropsError {
+    fn from(e: GameLumpError) -> Self {
+        Self::GameLump(e)
+    }
+}
+
+/// Size of a single prop record for a given game lump version
+fn prop_size(version: u16) -> Option<usize> {
+    Some(match version {
+        4 => 56,
+        5 => 60,
+        6 => 64,
+        7 | 8 => 68,
+        9 => 72,
+        10 => 76,
+        11 => 80,
+        _ => return None,
+    })
+}
+
+/// Little-endian cursor over a byte slice
+struct Reader<'d> {
+    data: &'d [u8],
+}
+
+impl<'d> Reader<'d> {
+    fn read<T: FromBytes>(&mut self) -> Result<T, StaticPropsError> {
+        let (value, rest) =
+            T::read_from_prefix(self.data).map_err(|_| StaticPropsError::Truncated)?;
+        self.data = rest;
+        Ok(value)
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'d [u8], StaticPropsError> {
+        if len > self.data.len() {
+            return Err(StaticPropsError::Truncated);
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    fn count(&mut self) -> Result<usize, StaticPropsError> {
+        usize::try_from(self.read::<i32>()?).map_err(|_| StaticPropsError::Truncated)
+    }
+}
+
+/// Decode a single prop record of the given version
+fn read_prop(record: &[u8], version: u16) -> Result<StaticProp, StaticPropsError> {
+    let mut r = Reader { data: record };
+    let mut prop = StaticProp {
+        origin: r.read()?,
+        angles: r.read()?,
+        prop_type: r.read()?,
+        first_leaf: r.read()?,
+        leaf_count: r.read()?,
+        solid: r.read()?,
+        flags: r.read()?,
+        skin: r.read()?,
+        fade_min_dist: r.read()?,
+        fade_max_dist: r.read()?,
+        lighting_origin: r.read()?,
+        forced_fade_scale: None,
+        dx_level: None,
+        cpu_level: None,
+        gpu_level: None,
+        diffuse_modulation: None,
+        disable_x360: None,
+        flags_ex: None,
+        uniform_scale: None,
+    };
+
+    if version >= 5 {
+        prop.forced_fade_scale = Some(r.read()?);
+    }
+    if matches!(version, 6 | 7) {
+        prop.dx_level = Some(r.read()?);
+    }
+    if version >= 8 {
+        prop.cpu_level = Some(r.read()?);
+        prop.gpu_level = Some(r.read()?);
+    }
+    if version >= 7 {
+        prop.diffuse_modulation = Some(r.read()?);
+    }
+    if version >= 9 {
+        // Stored as a 4-byte bool
+        prop.disable_x360 = Some(r.read::<u32>()? != 0);
+    }
+    if version >= 10 {
+        prop.flags_ex = Some(r.read()?);
+    }
+    if version >= 11 {
+        prop.uniform_scale = Some(r.read()?);
+    }
+    Ok(prop)
+}
+
+/// Decode the model name dictionary at the start of a static prop game lump
+fn read_names(r: &mut Reader<'_>) -> Result<Vec<String>, StaticPropsError> {
+    let count = r.count()?;
+    (0..count)
+        .map(|_| {
+            let name = r.bytes(MODEL_NAME_LEN)?;
+            let len = name.iter().position(|&b| b == 0).unwrap_or(name.len());
+            Ok(String::from_utf8_lossy(&name[..len]).into_owned())
+        })
+        .collect()
+}
+
+/// Decode the contents of a static prop game lump
+fn decode(data: &[u8], version: u16) -> Result<StaticProps, StaticPropsError> {
+    let prop_size = prop_size(version).ok_or(StaticPropsError::UnsupportedVersion(version))?;
+    let mut r = Reader { data };
+
+    let names = read_names(&mut r)?;
+
+    // The leaf array starts right after the last dictionary entry
+    let count = r.count()?;
+    let leaves = (0..count).map(|_| r.read()).collect::<Result<_, _>>()?;
+
+    let count = r.count()?;
+    let props = (0..count)
+        .map(|_| read_prop(r.bytes(prop_size)?, version))
+        .collect::<Result<_, _>>()?;
+
+    Ok(StaticProps {
+        version,
+        names,
+        leaves,
+        props,
+    })
+}
+
+impl Bsp<'_> {
+    /// Decode the static prop (`sprp`) game lump
+    pub fn static_props(&self) -> Result<StaticProps, StaticPropsError> {
+        self.with_static_props_data(decode)
+    }
+
+    /// Model names referenced by static props, deduplicated in dictionary order
+    ///
+    /// Only the dictionary is decoded, so this also works for prop versions
+    /// [`Bsp::static_props`] doesn't support.
+    pub fn static_prop_models(&self) -> Result<Vec<String>, StaticPropsError> {
+        self.with_static_props_data(|data, _| {
+            let mut names = read_names(&mut Reader { data })?;
+            let mut seen = alloc::collections::BTreeSet::new();
+            names.retain(|name| seen.insert(name.clone()));
+            Ok(names)
+        })
+    }
+
+    /// Call `f` with the decompressed data and version of the static prop game lump
+    fn with_static_props_data<R>(
+        &self,
+        f: impl FnOnce(&[u8], u16) -> Result<R, StaticPropsError>,
+    ) -> Result<R, StaticPropsError> {
+        let def = self
+            .game_lumps()?
+            .into_iter()
+            .find(|def| def.id == STATIC_PROPS_ID)
+            .ok_or(StaticPropsError::Missing)?;
+        let data = self.game_lump_data(&def)?;
+
+        #[cfg(feature = "lzma")]
+        if crate::lzma::is_compressed(&data) {
+            let data = crate::lzma::decompress(&data).map_err(|_| StaticPropsError::Compressed)?;
+            return f(&data, def.version);
+        }
+        #[cfg(not(feature = "lzma"))]
+        if data.starts_with(b"LZMA") {
+            return Err(StaticPropsError::Compressed);
+        }
+
+        f(&data, def.version)
+    }
+}
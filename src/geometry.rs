@@ -0,0 +1,198 @@
+use alloc::{vec, vec::Vec};
+
+use crate::{Bsp, Face, LumpId};
+
+/// Error returned by [`Bsp::model_triangles`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModelGeometryError {
+    /// Lump couldn't be cast to a slice of its records
+    Lump(LumpId),
+    /// Model index is out of range
+    ModelOutOfBounds(usize),
+    /// Face, surfedge, edge or vertex index is out of range of its lump
+    IndexOutOfBounds {
+        /// Lump the index points into
+        lump: LumpId,
+        /// Out of range index
+        index: i64,
+    },
+}
+
+impl core::fmt::Display for ModelGeometryError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Lump(lump) => write!(f, "{lump:?} lump is malformed"),
+            Self::ModelOutOfBounds(index) => write!(f, "model {index} is out of range"),
+            Self::IndexOutOfBounds { lump, index } => {
+                write!(f, "index {index} is out of range of the {lump:?} lump")
+            }
+        }
+    }
+}
+
+impl core::error::Error for ModelGeometryError {}
+
+/// What [`Bsp::export_obj`] and `Bsp::export_gltf` write
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportOptions {
+    /// Indices into [`LumpId::Models`](crate::LumpId::Models) to export, each as its own
+    /// object. Model 0 is the world.
+    pub models: Vec<usize>,
+    /// Include faces whose texture projection is
+    /// [`SurfaceFlags::NODRAW`](crate::SurfaceFlags::NODRAW)
+    pub include_nodraw: bool,
+    /// Share vertices between faces that have the same attributes
+    pub weld_vertices: bool,
+    /// Group faces by material: `usemtl` groups in OBJ, one primitive per material in
+    /// glTF
+    pub material_groups: bool,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            models: vec![0],
+            include_nodraw: false,
+            weld_vertices: true,
+            material_groups: true,
+        }
+    }
+}
+
+impl Bsp<'_> {
+    /// Triangles of every face of the model at `model_index`, with vertex positions in
+    /// world space
+    ///
+    /// Each face's polygon is rebuilt from its surfedges and triangulated as a fan,
+    /// keeping the surfedge winding. Faces with fewer than 3 edges are skipped, and
+    /// displacements are returned as their flat base face.
+    pub fn model_triangles(
+        &self,
+        model_index: usize,
+    ) -> Result<impl Iterator<Item = [[f32; 3]; 3]>, ModelGeometryError> {
+        let mut triangles = Vec::new();
+        self.model_polygons(model_index, |_, polygon| triangles.extend(fan(polygon)))?;
+        Ok(triangles.into_iter())
+    }
+
+    /// Extents of the map, from [`Bsp::bounding_box_from_model`] or, failing that,
+    /// [`Bsp::bounding_box_from_vertices`]
+    pub fn bounding_box(&self) -> Option<([f32; 3], [f32; 3])> {
+        self.bounding_box_from_model()
+            .or_else(|| self.bounding_box_from_vertices())
+    }
+
+    /// Extents of the world as stored in model 0's `mins` and `maxs`
+    ///
+    /// Returns `None` if there are no models or the stored bounds contain NaN or have a
+    /// minimum above its maximum.
+    pub fn bounding_box_from_model(&self) -> Option<([f32; 3], [f32; 3])> {
+        let models = self.models().ok()?;
+        let world = models.first()?;
+        (0..3)
+            .all(|i| world.mins[i] <= world.maxs[i])
+            .then_some((world.mins, world.maxs))
+    }
+
+    /// Extents of every vertex of [`LumpId::Vertexes`]
+    ///
+    /// Vertices with a NaN or infinite coordinate are skipped. Returns `None` if no
+    /// vertex is left.
+    pub fn bounding_box_from_vertices(&self) -> Option<([f32; 3], [f32; 3])> {
+        let vertices = self.vertices().ok()?;
+        vertices
+            .iter()
+            .map(|vertex| [vertex.x, vertex.y, vertex.z])
+            .filter(|position| position.iter().all(|c| c.is_finite()))
+            .fold(None, |bounds, position| {
+                let (mut mins, mut maxs) = bounds.unwrap_or((position, position));
+                for i in 0..3 {
+                    mins[i] = mins[i].min(position[i]);
+                    maxs[i] = maxs[i].max(position[i]);
+                }
+                Some((mins, maxs))
+            })
+    }
+
+    /// Call `f` with every face of the model at `model_index` that has at least 3 edges,
+    /// along with its polygon in surfedge order
+    pub(crate) fn model_polygons<F>(
+        &self,
+        model_index: usize,
+        mut f: F,
+    ) -> Result<(), ModelGeometryError>
+    where
+        F: FnMut(&Face, &[[f32; 3]]),
+    {
+        let out_of_bounds = |lump, index: i64| ModelGeometryError::IndexOutOfBounds { lump, index };
+
+        let models = self
+            .models()
+            .map_err(|_| ModelGeometryError::Lump(LumpId::Models))?;
+        let model = models
+            .get(model_index)
+            .ok_or(ModelGeometryError::ModelOutOfBounds(model_index))?;
+        let faces = self
+            .faces()
+            .map_err(|_| ModelGeometryError::Lump(LumpId::Faces))?;
+        let surfedges = self
+            .surfedges()
+            .map_err(|_| ModelGeometryError::Lump(LumpId::SurfEdges))?;
+        let edges = self
+            .edges()
+            .map_err(|_| ModelGeometryError::Lump(LumpId::Edges))?;
+        let vertices = self
+            .vertices()
+            .map_err(|_| ModelGeometryError::Lump(LumpId::Vertexes))?;
+
+        let mut polygon = Vec::new();
+        for face_index in self.model_faces(model) {
+            let face = faces
+                .get(face_index)
+                .ok_or(out_of_bounds(LumpId::Faces, face_index as i64))?;
+            if face.numedges < 3 {
+                continue;
+            }
+
+            polygon.clear();
+            for i in 0..face.numedges {
+                let index = i64::from(face.firstedge) + i64::from(i);
+                let surfedge = *usize::try_from(index)
+                    .ok()
+                    .and_then(|index| surfedges.get(index))
+                    .ok_or(out_of_bounds(LumpId::SurfEdges, index))?;
+
+                // Negative surfedges walk their edge backwards
+                let edge = edges
+                    .get(surfedge.unsigned_abs() as usize)
+                    .ok_or(out_of_bounds(LumpId::Edges, surfedge.into()))?;
+                let vertex = if surfedge < 0 { edge[1] } else { edge[0] };
+                let vertex = vertices
+                    .get(usize::from(vertex))
+                    .ok_or(out_of_bounds(LumpId::Vertexes, vertex.into()))?;
+                polygon.push([vertex.x, vertex.y, vertex.z]);
+            }
+
+            f(face, &polygon);
+        }
+        Ok(())
+    }
+}
+
+/// Triangulate a convex polygon as a fan around its first vertex
+pub(crate) fn fan<T: Copy>(polygon: &[T]) -> impl Iterator<Item = [T; 3]> + '_ {
+    polygon
+        .get(1..)
+        .unwrap_or_default()
+        .windows(2)
+        .map(|pair| [polygon[0], pair[0], pair[1]])
+}
+
+/// Triangulate a convex polygon like [`fan`], reversing each triangle to the
+/// counter-clockwise front-face winding of OBJ and glTF
+///
+/// Faces wind clockwise seen from the front.
+#[cfg(feature = "std")]
+pub(crate) fn fan_ccw<T: Copy>(polygon: &[T]) -> impl Iterator<Item = [T; 3]> + '_ {
+    fan(polygon).map(|[a, b, c]| [a, c, b])
+}
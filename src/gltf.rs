@@ -0,0 +1,346 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use std::collections::HashMap;
+
+use serde_json::{json, Value};
+
+use crate::{
+    geometry::fan_ccw, Bsp, ExportOptions, LumpId, ModelGeometryError, SurfaceFlags, TexInfo,
+};
+
+/// glTF is in meters, and a map unit is an inch
+const METERS_PER_UNIT: f32 = 0.0254;
+
+const GLB_MAGIC: &[u8; 4] = b"glTF";
+const GLB_VERSION: u32 = 2;
+const CHUNK_JSON: &[u8; 4] = b"JSON";
+const CHUNK_BIN: &[u8; 4] = b"BIN\0";
+
+const COMPONENT_FLOAT: u32 = 5126;
+const COMPONENT_UNSIGNED_INT: u32 = 5125;
+const TARGET_ARRAY_BUFFER: u32 = 34962;
+const TARGET_ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+/// Vertices and triangles sharing one material
+#[derive(Default)]
+struct Primitive {
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    uvs: Vec<[f32; 2]>,
+    indices: Vec<u32>,
+    // Welded vertices by the bits of their attributes, with -0.0 folded into 0.0
+    welded: HashMap<[u32; 8], u32>,
+}
+
+impl Primitive {
+    fn vertex(&mut self, weld: bool, position: [f32; 3], normal: [f32; 3], uv: [f32; 2]) -> u32 {
+        let mut key = [0; 8];
+        for (bits, c) in key
+            .iter_mut()
+            .zip(position.iter().chain(&normal).chain(&uv))
+        {
+            *bits = (c + 0.0).to_bits();
+        }
+        if let Some(&index) = self.welded.get(&key) {
+            return index;
+        }
+
+        let index = self.positions.len() as u32;
+        self.positions.push(position);
+        self.normals.push(normal);
+        self.uvs.push(uv);
+        if weld {
+            self.welded.insert(key, index);
+        }
+        index
+    }
+}
+
+/// Binary buffer along with the buffer views and accessors into it
+#[derive(Default)]
+struct Buffer {
+    bin: Vec<u8>,
+    views: Vec<Value>,
+    accessors: Vec<Value>,
+}
+
+impl Buffer {
+    /// Append `components` as a new accessor and return its index
+    fn push<T, const C: usize>(
+        &mut self,
+        components: &[[T; C]],
+        component_type: u32,
+        target: u32,
+        min_max: bool,
+    ) -> usize
+    where
+        T: Copy + PartialOrd + ToLeBytes + Into<Value>,
+    {
+        let offset = self.bin.len();
+        for component in components.iter().flatten() {
+            component.write_le(&mut self.bin);
+        }
+        self.views.push(json!({
+            "buffer": 0,
+            "byteOffset": offset,
+            "byteLength": self.bin.len() - offset,
+            "target": target,
+        }));
+
+        let kind = match C {
+            1 => "SCALAR",
+            2 => "VEC2",
+            _ => "VEC3",
+        };
+        let mut accessor = json!({
+            "bufferView": self.views.len() - 1,
+            "componentType": component_type,
+            "count": components.len(),
+            "type": kind,
+        });
+        if min_max {
+            let mut min = components[0];
+            let mut max = components[0];
+            for component in &components[1..] {
+                for i in 0..C {
+                    if component[i] < min[i] {
+                        min[i] = component[i];
+                    }
+                    if component[i] > max[i] {
+                        max[i] = component[i];
+                    }
+                }
+            }
+            accessor["min"] = min.into_iter().map(Into::into).collect();
+            accessor["max"] = max.into_iter().map(Into::into).collect();
+        }
+        self.accessors.push(accessor);
+        self.accessors.len() - 1
+    }
+}
+
+trait ToLeBytes {
+    fn write_le(self, out: &mut Vec<u8>);
+}
+
+impl ToLeBytes for f32 {
+    fn write_le(self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+}
+
+impl ToLeBytes for u32 {
+    fn write_le(self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+}
+
+/// Map Z-up coordinates onto glTF's Y-up axes
+fn y_up([x, y, z]: [f32; 3]) -> [f32; 3] {
+    [x, z, -y]
+}
+
+fn dot(a: [f32; 3], b: &[f32; 4]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+impl Bsp<'_> {
+    /// Export the faces of the selected models as a binary glTF (`.glb`) file
+    ///
+    /// Every model becomes a node with its own mesh, triangulated like
+    /// [`Bsp::export_obj`]. Vertices carry the normal of the face's plane and UVs
+    /// from its texture projection, normalized by the texture size. Positions are
+    /// converted to meters with Y up, and each material is referenced by name.
+    /// Models without any exported faces are left out.
+    pub fn export_gltf(&self, options: &ExportOptions) -> Result<Vec<u8>, ModelGeometryError> {
+        let planes = self
+            .planes()
+            .map_err(|_| ModelGeometryError::Lump(LumpId::Planes))?;
+        let texinfo = self.texinfo().ok();
+        let texdata = self.texdata().ok();
+
+        let mut materials: Vec<String> = Vec::new();
+        let mut material_indices: HashMap<String, usize> = HashMap::new();
+        let mut buffer = Buffer::default();
+        let mut meshes = Vec::new();
+        let mut nodes = Vec::new();
+
+        for &model in &options.models {
+            let mut polygons = Vec::new();
+            self.model_polygons(model, |face, polygon| {
+                polygons.push((*face, polygon.to_vec()));
+            })?;
+
+            let mut primitives: Vec<(Option<usize>, Primitive)> = Vec::new();
+            for (face, polygon) in polygons {
+                let info = usize::try_from(face.texinfo)
+                    .ok()
+                    .and_then(|index| texinfo.as_ref()?.get(index));
+                let flags = info.map(TexInfo::surface_flags).unwrap_or_default();
+                if !options.include_nodraw && flags.contains(SurfaceFlags::NODRAW) {
+                    continue;
+                }
+
+                let material = options.material_groups.then(|| {
+                    let name = usize::try_from(face.texinfo)
+                        .ok()
+                        .and_then(|index| self.material_name(index))
+                        .map(|name| name.to_string())
+                        .unwrap_or_else(|| format!("texinfo_{}", face.texinfo));
+                    *material_indices.entry(name).or_insert_with_key(|name| {
+                        materials.push(name.clone());
+                        materials.len() - 1
+                    })
+                });
+                let primitive = match primitives.iter().position(|(m, _)| *m == material) {
+                    Some(index) => &mut primitives[index].1,
+                    None => {
+                        primitives.push((material, Primitive::default()));
+                        &mut primitives.last_mut().unwrap().1
+                    }
+                };
+
+                let plane = planes.get(usize::from(face.planenum)).ok_or(
+                    ModelGeometryError::IndexOutOfBounds {
+                        lump: LumpId::Planes,
+                        index: face.planenum.into(),
+                    },
+                )?;
+                let mut normal = plane.normal;
+                if face.side != 0 {
+                    normal = normal.map(|c| -c);
+                }
+
+                let size = info
+                    .and_then(|info| texdata.as_ref()?.get(usize::try_from(info.texdata).ok()?))
+                    .map(|texdata| [texdata.width, texdata.height])
+                    .filter(|size| size.iter().all(|&c| c > 0))
+                    .map_or([1.0; 2], |size| size.map(|c| c as f32));
+
+                let indices: Vec<u32> = polygon
+                    .iter()
+                    .map(|&position| {
+                        let uv = match info {
+                            Some(info) => [
+                                (dot(position, &info.texture_vecs[0]) + info.texture_vecs[0][3])
+                                    / size[0],
+                                (dot(position, &info.texture_vecs[1]) + info.texture_vecs[1][3])
+                                    / size[1],
+                            ],
+                            None => [0.0; 2],
+                        };
+                        primitive.vertex(
+                            options.weld_vertices,
+                            y_up(position.map(|c| c * METERS_PER_UNIT)),
+                            y_up(normal),
+                            uv,
+                        )
+                    })
+                    .collect();
+
+                primitive.indices.extend(fan_ccw(&indices).flatten());
+            }
+
+            if primitives.is_empty() {
+                continue;
+            }
+
+            let primitives: Vec<Value> = primitives
+                .into_iter()
+                .map(|(material, primitive)| {
+                    let positions = buffer.push(
+                        &primitive.positions,
+                        COMPONENT_FLOAT,
+                        TARGET_ARRAY_BUFFER,
+                        true,
+                    );
+                    let normals = buffer.push(
+                        &primitive.normals,
+                        COMPONENT_FLOAT,
+                        TARGET_ARRAY_BUFFER,
+                        false,
+                    );
+                    let uvs =
+                        buffer.push(&primitive.uvs, COMPONENT_FLOAT, TARGET_ARRAY_BUFFER, false);
+                    let indices: Vec<[u32; 1]> = primitive.indices.iter().map(|&i| [i]).collect();
+                    let indices = buffer.push(
+                        &indices,
+                        COMPONENT_UNSIGNED_INT,
+                        TARGET_ELEMENT_ARRAY_BUFFER,
+                        false,
+                    );
+
+                    let mut value = json!({
+                        "attributes": {
+                            "POSITION": positions,
+                            "NORMAL": normals,
+                            "TEXCOORD_0": uvs,
+                        },
+                        "indices": indices,
+                    });
+                    if let Some(material) = material {
+                        value["material"] = material.into();
+                    }
+                    value
+                })
+                .collect();
+
+            nodes.push(json!({ "name": format!("model_{model}"), "mesh": meshes.len() }));
+            meshes.push(json!({ "name": format!("model_{model}"), "primitives": primitives }));
+        }
+
+        // glTF forbids empty arrays, so anything without entries is left out
+        let mut document = json!({
+            "asset": { "version": "2.0", "generator": env!("CARGO_PKG_NAME") },
+        });
+        if !nodes.is_empty() {
+            document["scene"] = 0.into();
+            document["scenes"] = json!([{ "nodes": (0..nodes.len()).collect::<Vec<_>>() }]);
+            document["nodes"] = nodes.into();
+            document["meshes"] = meshes.into();
+        }
+        if !materials.is_empty() {
+            document["materials"] = materials
+                .iter()
+                .map(|name| json!({ "name": name }))
+                .collect();
+        }
+        if !buffer.bin.is_empty() {
+            document["buffers"] = json!([{ "byteLength": buffer.bin.len() }]);
+            document["bufferViews"] = buffer.views.into();
+            document["accessors"] = buffer.accessors.into();
+        }
+
+        Ok(glb(document.to_string().into_bytes(), buffer.bin))
+    }
+}
+
+/// Assemble a GLB container from its JSON and binary chunks
+fn glb(mut json: Vec<u8>, mut bin: Vec<u8>) -> Vec<u8> {
+    // Chunks are 4-byte aligned, JSON padded with spaces and binary with zeros
+    json.resize(json.len().next_multiple_of(4), b' ');
+    bin.resize(bin.len().next_multiple_of(4), 0);
+
+    let mut length = 12 + 8 + json.len();
+    if !bin.is_empty() {
+        length += 8 + bin.len();
+    }
+
+    let mut out = Vec::with_capacity(length);
+    out.extend_from_slice(GLB_MAGIC);
+    out.extend_from_slice(&GLB_VERSION.to_le_bytes());
+    out.extend_from_slice(&(length as u32).to_le_bytes());
+    for (kind, chunk) in [(CHUNK_JSON, json), (CHUNK_BIN, bin)] {
+        if chunk.is_empty() {
+            continue;
+        }
+        out.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+        out.extend_from_slice(kind);
+        out.extend_from_slice(&chunk);
+    }
+    out
+}
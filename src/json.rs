@@ -0,0 +1,61 @@
+use alloc::{string::String, vec::Vec};
+
+use serde::{Serialize, Serializer};
+
+use crate::Bsp;
+
+/// Serialize an array of any length as a sequence, since serde only covers up to 32
+pub(crate) fn serialize_array<S, T, const N: usize>(
+    array: &[T; N],
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: Serialize,
+{
+    serializer.collect_seq(array)
+}
+
+#[derive(Serialize)]
+struct Directory {
+    identifier: [u8; 4],
+    version: u32,
+    revision: i32,
+    lumps: Vec<DirectoryEntry>,
+}
+
+#[derive(Serialize)]
+struct DirectoryEntry {
+    index: usize,
+    offset: u32,
+    length: u32,
+    version: u32,
+    fourcc: [u8; 4],
+}
+
+impl Bsp<'_> {
+    /// Header and lump directory as JSON, without any lump data
+    ///
+    /// Entries describe the file as it was parsed, so lump edits aren't reflected.
+    pub fn directory_json(&self) -> String {
+        let directory = Directory {
+            identifier: self.header.identifier,
+            version: self.header.version,
+            revision: self.header.revision,
+            lumps: self
+                .header
+                .lump_defs
+                .iter()
+                .enumerate()
+                .map(|(index, def)| DirectoryEntry {
+                    index,
+                    offset: def.offset,
+                    length: def.length,
+                    version: def.metadata.version,
+                    fourcc: def.metadata.identifier,
+                })
+                .collect(),
+        };
+        serde_json::to_string(&directory).expect("directory serialization can't fail")
+    }
+}
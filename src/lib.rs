@@ -1,17 +1,90 @@
-use std::{
-    borrow::{Borrow, Cow},
-    cell::{Ref, RefCell, RefMut},
-    mem::MaybeUninit,
+#![no_std]
+
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+#[cfg(not(feature = "alloc"))]
+compile_error!("the `alloc` feature is required");
+
+use alloc::{borrow::Cow, string::String, vec, vec::Vec};
+use core::{
+    borrow::Borrow,
+    cell::{Cell, Ref, RefCell, RefMut},
+    marker::PhantomData,
+    ops::{Deref, Range},
 };
 
-use zerocopy::{CastError, FromBytes, Immutable, IntoBytes, KnownLayout};
-use zerocopy_derive::*;
+use zerocopy::{ConvertError, FromBytes, FromZeros, Immutable, IntoBytes, KnownLayout};
+
+use sealed::LumpEntry;
+
+mod builder;
+#[cfg(feature = "checksum")]
+mod checksum;
+mod diff;
+mod endian;
+mod entities;
+mod flags;
+mod flavor;
+mod game_lump;
+mod geometry;
+#[cfg(feature = "gltf")]
+mod gltf;
+#[cfg(feature = "serde")]
+mod json;
+mod lightmap;
+mod lump_file;
+mod lump_id;
+mod lumps;
+#[cfg(feature = "lzma")]
+mod lzma;
+#[cfg(feature = "std")]
+mod obj;
+mod owned;
+mod pakfile;
+mod phys_collide;
+mod quake;
+mod respawn;
+mod validate;
+mod view;
+mod visibility;
+
+pub use builder::BspBuilder;
+pub use diff::{BspDiff, LumpDiff};
+pub use endian::Endianness;
+pub use entities::{Entity, EntityParseError};
+pub use flags::{Contents, SurfaceFlags};
+pub use flavor::{AnyBsp, BspFlavor};
+pub use game_lump::{
+    GameLumpDef, GameLumpError, StaticProp, StaticProps, StaticPropsError, STATIC_PROPS_ID,
+};
+pub use geometry::{ExportOptions, ModelGeometryError};
+pub use lightmap::{ColorRgbExp32, Lightmap};
+pub use lump_file::{LumpFileError, LumpFileHeader};
+pub use lump_id::{InvalidLumpId, LumpId};
+pub use lumps::*;
+#[cfg(feature = "lzma")]
+pub use lzma::{CompressionPolicy, DecompressError, LZMA_ID};
+#[cfg(feature = "mmap")]
+pub use owned::MmapBsp;
+pub use owned::OwnedBsp;
+pub use phys_collide::{PhysDisp, PhysDispError, PhysModel, PhysModelError};
+pub use quake::{GoldSrcBsp, QuakeBsp, QuakeHeader, QuakeLumpDef, QUAKE_LUMP_DEF_COUNT};
+pub use respawn::{RespawnBsp, RespawnHeader, RESPAWN_LUMP_DEF_COUNT};
+pub use validate::{ValidationIssue, ValidationOptions, ValidationReport};
+pub use view::{BspFileView, BspView};
+pub use visibility::{Visibility, VisibilityError};
 
 /// Lump definition count
 pub const LUMP_DEF_COUNT: usize = 64;
 
+/// Byte boundary lumps are aligned to when writing, matching the engine's tools
+pub const LUMP_ALIGNMENT: usize = 4;
+
 /// BSP lump metadata
 #[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[repr(C)]
 pub struct LumpMetadata {
     /// Lump version
@@ -22,6 +95,7 @@ pub struct LumpMetadata {
 
 /// BSP lump definition
 #[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[repr(C)]
 pub struct LumpDef {
     /// Absolute offset in file
@@ -31,8 +105,26 @@ pub struct LumpDef {
     metadata: LumpMetadata,
 }
 
+impl LumpDef {
+    /// Absolute offset in file
+    pub fn offset(&self) -> u32 {
+        self.offset
+    }
+
+    /// Length of data
+    pub fn length(&self) -> u32 {
+        self.length
+    }
+
+    /// Lump metadata
+    pub fn metadata(&self) -> LumpMetadata {
+        self.metadata
+    }
+}
+
 /// BSP file header
 #[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[repr(C)]
 pub struct Header {
     /// File format identifier
@@ -40,51 +132,494 @@ pub struct Header {
     /// File format version
     pub version: u32,
     /// Lump definitions
+    #[cfg_attr(feature = "serde", serde(serialize_with = "json::serialize_array"))]
     lump_defs: [LumpDef; LUMP_DEF_COUNT],
     /// File revision
     pub revision: i32,
 }
 
-// TODO: describe
-type LumpCell<'a> = RefCell<(Cow<'a, LumpMetadata>, Cow<'a, [u8]>)>;
+impl Header {
+    /// Lump definitions
+    pub fn lump_defs(&self) -> &[LumpDef; LUMP_DEF_COUNT] {
+        &self.lump_defs
+    }
+}
+
+// Lump offsets are computed from the header size, which must match the on-disk layout
+// (4 + 4 + 64 * 16 + 4 bytes)
+const _: () = assert!(size_of::<Header>() == 1036);
+
+/// Header layout of a supported BSP format with `N` lump definitions
+pub trait BspHeader<const N: usize>:
+    sealed::Directory<N> + FromBytes + IntoBytes + KnownLayout + Immutable + Clone
+{
+}
+
+impl<H, const N: usize> BspHeader<N> for H where
+    H: sealed::Directory<N> + FromBytes + IntoBytes + KnownLayout + Immutable + Clone
+{
+}
+
+mod sealed {
+    use crate::{BspFlavor, LumpMetadata};
+
+    /// Access to the parts of a header shared by every format
+    pub trait Directory<const N: usize> {
+        /// Lump directory entry
+        type LumpDef: LumpEntry;
+
+        /// Flavors whose magic this header is expected to carry
+        const FLAVORS: &'static [BspFlavor];
+
+        /// Index of the lump holding a game lump directory, whose entries are absolute
+        /// offsets into the file
+        const GAME_LUMP: Option<usize> = None;
+
+        fn magic(&self) -> [u8; 4];
+        fn directory(&self) -> &[Self::LumpDef; N];
+        fn directory_mut(&mut self) -> &mut [Self::LumpDef; N];
+    }
+
+    /// Lump directory entry of any format
+    pub trait LumpEntry: Copy {
+        fn offset(&self) -> u32;
+        fn length(&self) -> u32;
+        /// Metadata, if the format stores any
+        fn metadata(&self) -> Option<&LumpMetadata>;
+        fn set_range(&mut self, offset: u32, length: u32);
+        /// Ignored by formats without metadata
+        fn set_metadata(&mut self, metadata: LumpMetadata);
+    }
+}
+
+impl sealed::LumpEntry for LumpDef {
+    fn offset(&self) -> u32 {
+        self.offset
+    }
+
+    fn length(&self) -> u32 {
+        self.length
+    }
+
+    fn metadata(&self) -> Option<&LumpMetadata> {
+        Some(&self.metadata)
+    }
+
+    fn set_range(&mut self, offset: u32, length: u32) {
+        self.offset = offset;
+        self.length = length;
+    }
+
+    fn set_metadata(&mut self, metadata: LumpMetadata) {
+        self.metadata = metadata;
+    }
+}
+
+impl sealed::Directory<LUMP_DEF_COUNT> for Header {
+    type LumpDef = LumpDef;
+
+    const FLAVORS: &'static [BspFlavor] = &[BspFlavor::Source];
+
+    const GAME_LUMP: Option<usize> = Some(LumpId::GameLump as usize);
+
+    fn magic(&self) -> [u8; 4] {
+        self.identifier
+    }
+
+    fn directory(&self) -> &[LumpDef; LUMP_DEF_COUNT] {
+        &self.lump_defs
+    }
+
+    fn directory_mut(&mut self) -> &mut [LumpDef; LUMP_DEF_COUNT] {
+        &mut self.lump_defs
+    }
+}
+
+/// Lump metadata and data, along with whether they were handed out mutably
+struct LumpCell<'a> {
+    lump: RefCell<(Cow<'a, LumpMetadata>, Cow<'a, [u8]>)>,
+    dirty: Cell<bool>,
+}
+
+impl<'a> LumpCell<'a> {
+    fn new(metadata: Cow<'a, LumpMetadata>, data: Cow<'a, [u8]>) -> Self {
+        Self {
+            lump: RefCell::new((metadata, data)),
+            dirty: Cell::new(false),
+        }
+    }
+
+    fn get(&self) -> LumpRef<'a, '_> {
+        Ref::map_split(self.lump.borrow(), |v| (&v.0, &v.1))
+    }
+
+    fn get_mut(&self) -> LumpRefMut<'a, '_> {
+        let lump = RefMut::map_split(self.lump.borrow_mut(), |v| (&mut v.0, &mut v.1));
+        self.dirty.set(true);
+        lump
+    }
+
+    fn cast<T>(&self, index: usize) -> Result<Ref<'_, T>, LumpCastError<T>>
+    where
+        T: ?Sized + FromBytes + KnownLayout + Immutable,
+    {
+        let lump = self.lump.borrow();
+        let len = lump.1.len();
+        let mut kind = LumpCastErrorKind::Size;
+        Ref::filter_map(lump, |v| {
+            let data: &[u8] = if v.1.is_empty() {
+                aligned_empty()
+            } else {
+                &v.1
+            };
+            T::ref_from_bytes(data)
+                .map_err(|e| kind = LumpCastErrorKind::from_convert(&e))
+                .ok()
+        })
+        .map_err(|_| LumpCastError::new(index, len, kind))
+    }
+
+    fn cast_mut<T>(&self, index: usize) -> Result<RefMut<'_, T>, LumpCastError<T>>
+    where
+        T: ?Sized + FromBytes + IntoBytes + KnownLayout + Immutable,
+    {
+        let lump = self.lump.borrow_mut();
+        let len = lump.1.len();
+        let mut kind = LumpCastErrorKind::Size;
+        let lump = RefMut::filter_map(lump, |v| {
+            let data = if v.1.is_empty() {
+                aligned_empty()
+            } else {
+                v.1.to_mut()
+            };
+            T::mut_from_bytes(data)
+                .map_err(|e| kind = LumpCastErrorKind::from_convert(&e))
+                .ok()
+        })
+        .map_err(|_| LumpCastError::new(index, len, kind))?;
+
+        self.dirty.set(true);
+        Ok(lump)
+    }
+}
+
+/// Empty slice that casts to any lump type
+///
+/// An empty `Vec<u8>` has a dangling pointer that's only aligned for bytes, so casting an
+/// empty owned lump would otherwise fail with [`LumpCastErrorKind::Alignment`].
+fn aligned_empty<'b>() -> &'b mut [u8] {
+    let empty: &mut [u64] = &mut [];
+    empty.as_mut_bytes()
+}
 
 type LumpRef<'a, 'b> = (Ref<'b, Cow<'a, LumpMetadata>>, Ref<'b, Cow<'a, [u8]>>);
 type LumpRefMut<'a, 'b> = (RefMut<'b, Cow<'a, LumpMetadata>>, RefMut<'b, Cow<'a, [u8]>>);
 
-/// Representation of a BSP file
-pub struct Bsp<'a> {
+/// Lump bytes, either borrowed from the [`Bsp`] or decompressed into an owned buffer
+pub enum LumpData<'b> {
+    /// Lump was stored uncompressed
+    Raw(Ref<'b, [u8]>),
+    /// Lump was decompressed (only produced with the `lzma` feature)
+    Decompressed(Vec<u8>),
+}
+
+impl Deref for LumpData<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Self::Raw(data) => data,
+            Self::Decompressed(data) => data,
+        }
+    }
+}
+
+impl AsRef<[u8]> for LumpData<'_> {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+/// Error returned by [`Bsp::parse`]
+#[derive(Debug)]
+pub enum ParseError {
+    /// Data is misaligned for the header
+    HeaderMisaligned,
+    /// Data is too short to contain the header
+    HeaderTooShort,
+    /// Lump data extends past the end of the file
+    LumpOutOfBounds {
+        /// Lump index
+        index: usize,
+        /// Absolute offset in file
+        offset: usize,
+        /// Length of data
+        length: usize,
+        /// Length of file
+        file_len: usize,
+    },
+    /// Non-empty lump starts inside the header
+    OffsetInsideHeader {
+        /// Lump index
+        index: usize,
+    },
+    /// Lump offset and length overflow when added together
+    OffsetOverflow {
+        /// Lump index
+        index: usize,
+    },
+    /// File magic doesn't match the header layout (only returned by [`BspFile::parse_strict`])
+    BadMagic([u8; 4]),
+}
+
+impl core::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::HeaderMisaligned => f.write_str("header is misaligned"),
+            Self::HeaderTooShort => f.write_str("file is too short to contain a header"),
+            Self::LumpOutOfBounds {
+                index,
+                offset,
+                length,
+                file_len,
+            } => write!(
+                f,
+                "lump {index} ({length} bytes at offset {offset}) extends past end of file ({file_len} bytes)"
+            ),
+            Self::OffsetInsideHeader { index } => write!(f, "lump {index} starts inside header"),
+            Self::OffsetOverflow { index } => write!(f, "lump {index} offset overflows"),
+            Self::BadMagic(magic) => write!(
+                f,
+                "unrecognized file identifier {:?}",
+                String::from_utf8_lossy(magic)
+            ),
+        }
+    }
+}
+
+impl core::error::Error for ParseError {}
+
+/// Error returned when a lump can't be cast to `T`
+///
+/// The name and size of `T` are available through [`LumpCastError::type_name`] and
+/// [`LumpCastError::size`] (or [`LumpCastError::element_size`] for slices).
+pub struct LumpCastError<T: ?Sized> {
+    /// Lump index
+    pub index: usize,
+    /// Lump length in bytes
+    pub len: usize,
+    /// Reason the cast failed
+    pub kind: LumpCastErrorKind,
+    _type: PhantomData<fn() -> *const T>,
+}
+
+/// Reason a [`LumpCastError`] occurred
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LumpCastErrorKind {
+    /// Lump length doesn't fit the type, e.g. isn't a multiple of the element size
+    Size,
+    /// Lump data isn't aligned for the type
+    Alignment,
+}
+
+/// What [`Bsp::lump_many_with`] does with bytes after the last whole element
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TrailingPolicy {
+    /// Fail with [`LumpCastErrorKind::Size`] unless the length is a multiple of the
+    /// element size
+    #[default]
+    Strict,
+    /// Cast `len / size_of::<T>()` elements and ignore the rest, for compilers that pad
+    /// lumps past their last element
+    IgnoreRemainder,
+}
+
+/// Cast `data` to as many `T` as it holds, ignoring any bytes past the last one
+fn cast_prefix<T>(data: &[u8]) -> Result<&[T], LumpCastErrorKind>
+where
+    T: FromBytes + KnownLayout + Immutable,
+{
+    let data: &[u8] = if data.is_empty() {
+        aligned_empty()
+    } else {
+        data
+    };
+    <[T]>::ref_from_prefix(data)
+        .map(|(elements, _)| elements)
+        .map_err(|e| LumpCastErrorKind::from_convert(&e))
+}
+
+impl LumpCastErrorKind {
+    fn from_convert<A, S>(error: &ConvertError<A, S, core::convert::Infallible>) -> Self {
+        match error {
+            ConvertError::Alignment(_) => Self::Alignment,
+            ConvertError::Size(_) => Self::Size,
+            ConvertError::Validity(v) => match *v {},
+        }
+    }
+}
+
+impl<T: ?Sized> LumpCastError<T> {
+    fn new(index: usize, len: usize, kind: LumpCastErrorKind) -> Self {
+        Self {
+            index,
+            len,
+            kind,
+            _type: PhantomData,
+        }
+    }
+
+    /// Name of the type the lump was cast to
+    pub fn type_name(&self) -> &'static str {
+        core::any::type_name::<T>()
+    }
+}
+
+impl<T> LumpCastError<T> {
+    /// Size of the type in bytes
+    pub fn size(&self) -> usize {
+        size_of::<T>()
+    }
+}
+
+impl<T> LumpCastError<[T]> {
+    /// Size of a slice element in bytes
+    pub fn element_size(&self) -> usize {
+        size_of::<T>()
+    }
+}
+
+impl<T: ?Sized> Clone for LumpCastError<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: ?Sized> Copy for LumpCastError<T> {}
+
+impl<T: ?Sized> core::fmt::Debug for LumpCastError<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("LumpCastError")
+            .field("type", &self.type_name())
+            .field("index", &self.index)
+            .field("len", &self.len)
+            .field("kind", &self.kind)
+            .finish()
+    }
+}
+
+impl<T: ?Sized> core::fmt::Display for LumpCastError<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let (index, len, name) = (self.index, self.len, self.type_name());
+        match self.kind {
+            LumpCastErrorKind::Size => {
+                write!(
+                    f,
+                    "lump {index} ({len} bytes) doesn't fit the size of `{name}`"
+                )
+            }
+            LumpCastErrorKind::Alignment => {
+                write!(f, "lump {index} is misaligned for `{name}`")
+            }
+        }
+    }
+}
+
+impl<T: ?Sized> core::error::Error for LumpCastError<T> {}
+
+/// Error returned by [`BspFile::write_to_slice`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WriteError {
+    /// Buffer is shorter than the serialized file
+    BufferTooSmall {
+        /// Length of the serialized file
+        needed: usize,
+    },
+}
+
+impl core::fmt::Display for WriteError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::BufferTooSmall { needed } => {
+                write!(f, "buffer is too small, {needed} bytes are needed")
+            }
+        }
+    }
+}
+
+impl core::error::Error for WriteError {}
+
+/// Representation of a BSP file with header `H` and `N` lumps
+///
+/// Lumps live in `RefCell`s so that they can be edited through `&self`, which makes a
+/// `BspFile` `Send` but not `Sync`: it can be moved to another thread, but not shared
+/// between threads. Parsing borrows the input instead of copying it, so to read lumps
+/// from several threads, such as when processing faces in parallel, share the file's
+/// bytes and [`BspFile::parse`] them once per thread.
+pub struct BspFile<'a, H, const N: usize>
+where
+    H: BspHeader<N>,
+{
     /// BSP Header
-    pub header: Cow<'a, Header>,
-    /// Array of [`LUMP_DEF_COUNT`] [`LumpPair`]'s
-    lumps: [LumpCell<'a>; LUMP_DEF_COUNT],
-}
-
-impl<'a> Bsp<'a> {
-    pub fn parse(data: &'a [u8]) -> Result<Self, CastError<&'a [u8], Header>> {
-        let (header, data) = Header::ref_from_prefix(data)?;
-
-        // Construct array of (&'a mut LumpMetadata, Cow<'a, [u8]>) from lump entries
-        let lumps = header.lump_defs.each_ref().map(
-            |&LumpDef {
-                 offset,
-                 length,
-                 ref metadata,
-             }| {
-                const HEADER_SIZE: usize = size_of::<Header>();
-                let (offset, length) = (offset as usize, length as usize);
-
-                // Adjust offset by HEADER_SIZE since LumpDef's offset field is an absolute
-                // offset in file and we're indexing relative to the end of the header
-                let offset = offset.saturating_sub(HEADER_SIZE);
-
-                assert!((offset + length) <= data.len());
-
-                RefCell::new((
-                    Cow::Borrowed(metadata),
-                    Cow::Borrowed(&data[offset..offset + length]),
-                ))
-            },
-        );
+    pub header: Cow<'a, H>,
+    /// Array of `N` lump cells
+    lumps: [LumpCell<'a>; N],
+}
+
+/// Source engine BSP file
+pub type Bsp<'a> = BspFile<'a, Header, LUMP_DEF_COUNT>;
+
+const _: () = {
+    const fn assert_send<T: Send>() {}
+    assert_send::<Bsp<'static>>();
+    assert_send::<QuakeBsp<'static>>();
+    assert_send::<RespawnBsp<'static>>();
+};
+
+impl<'a, H, const N: usize> BspFile<'a, H, N>
+where
+    H: BspHeader<N>,
+{
+    /// Size of the header in bytes, which is where lump data can start
+    pub fn header_size() -> usize {
+        size_of::<H>()
+    }
+
+    /// Parse a BSP file, borrowing lumps from `data`
+    ///
+    /// This is the canonical constructor. Parsing never mutates `data`, so it only needs a
+    /// shared borrow. See [`OwnedBsp`] for keeping the buffer and the [`Bsp`] together.
+    ///
+    /// Malformed input is always reported as a [`ParseError`], never a panic.
+    pub fn parse(data: &'a [u8]) -> Result<Self, ParseError> {
+        let file_len = data.len();
+        let (header, data) = H::ref_from_prefix(data).map_err(|e| match e {
+            ConvertError::Alignment(_) => ParseError::HeaderMisaligned,
+            ConvertError::Size(_) => ParseError::HeaderTooShort,
+            ConvertError::Validity(v) => match v {},
+        })?;
+
+        // Build every cell in a single pass over the directory. A malformed entry gets an
+        // empty cell and is reported once the array is complete, so nothing can panic
+        let mut error = None;
+        let lumps = core::array::from_fn(|i| {
+            let def = &header.directory()[i];
+            let metadata = match def.metadata() {
+                Some(metadata) => Cow::Borrowed(metadata),
+                None => Cow::Owned(LumpMetadata::new_zeroed()),
+            };
+            let data = match Self::checked_lump_range(def, i, file_len) {
+                Ok(range) => &data[range],
+                Err(e) => {
+                    error.get_or_insert(e);
+                    &[]
+                }
+            };
+            LumpCell::new(metadata, Cow::Borrowed(data))
+        });
+        if let Some(error) = error {
+            return Err(error);
+        }
 
         let bsp = Self {
             header: Cow::Borrowed(header),
@@ -93,99 +628,662 @@ impl<'a> Bsp<'a> {
         Ok(bsp)
     }
 
-    pub fn write_to_io<W>(&self, mut writer: W) -> std::io::Result<()>
+    /// Byte range of every lump relative to the end of the header, checked against a file
+    /// of `file_len` bytes
+    pub(crate) fn lump_ranges(
+        header: &H,
+        file_len: usize,
+    ) -> Result<[Range<usize>; N], ParseError> {
+        let mut ranges = [const { 0..0 }; N];
+        for (index, (def, range)) in header.directory().iter().zip(&mut ranges).enumerate() {
+            *range = Self::checked_lump_range(def, index, file_len)?;
+        }
+        Ok(ranges)
+    }
+
+    /// Byte range of the lump at `index` described by `def`, relative to the end of the
+    /// header and checked against a file of `file_len` bytes
+    fn checked_lump_range(
+        def: &H::LumpDef,
+        index: usize,
+        file_len: usize,
+    ) -> Result<Range<usize>, ParseError> {
+        let header_size = size_of::<H>();
+        let (offset, length) = (def.offset() as usize, def.length() as usize);
+
+        // Empty lumps are left as an empty range regardless of their stored offset,
+        // since some compilers write zero (or otherwise bogus) offsets for them
+        if length == 0 {
+            return Ok(0..0);
+        }
+
+        // Adjust offset by the header size since LumpDef's offset field is an absolute
+        // offset in file and we're indexing relative to the end of the header
+        let start = offset
+            .checked_sub(header_size)
+            .ok_or(ParseError::OffsetInsideHeader { index })?;
+        let end = start
+            .checked_add(length)
+            .ok_or(ParseError::OffsetOverflow { index })?;
+
+        if end > file_len.saturating_sub(header_size) {
+            return Err(ParseError::LumpOutOfBounds {
+                index,
+                offset,
+                length,
+                file_len,
+            });
+        }
+        Ok(start..end)
+    }
+
+    /// Detach from the parsed buffer by taking ownership of the header and every lump
+    pub fn into_owned(self) -> BspFile<'static, H, N>
+    where
+        H: 'static,
+    {
+        BspFile {
+            header: Cow::Owned(self.header.into_owned()),
+            lumps: self.lumps.map(|cell| {
+                let (metadata, data) = cell.lump.into_inner();
+                LumpCell {
+                    lump: RefCell::new((
+                        Cow::Owned(metadata.into_owned()),
+                        Cow::Owned(data.into_owned()),
+                    )),
+                    dirty: cell.dirty,
+                }
+            }),
+        }
+    }
+
+    /// Take ownership of the header and every lump in place, so nothing refers to the
+    /// parsed buffer anymore
+    ///
+    /// Every lump that's still borrowed is copied into its own allocation, which costs as
+    /// much memory as the lumps themselves. Lumps that are already owned are left as is.
+    /// The lifetime stays the same, use [`BspFile::into_owned`] to get a `'static` value.
+    pub fn detach(&mut self) {
+        self.header.to_mut();
+        for cell in &mut self.lumps {
+            let (metadata, data) = cell.lump.get_mut();
+            metadata.to_mut();
+            data.to_mut();
+        }
+    }
+
+    /// Copy the header and every lump into a [`Bsp`] that doesn't borrow the parsed buffer
+    ///
+    /// Panics if any lump is currently borrowed mutably.
+    pub fn to_owned(&self) -> BspFile<'static, H, N>
+    where
+        H: 'static,
+    {
+        self.deep_copy()
+    }
+
+    /// Copy the header and every lump into owned buffers, with any lifetime
+    fn deep_copy<'b>(&self) -> BspFile<'b, H, N>
+    where
+        H: 'b,
+    {
+        BspFile {
+            header: Cow::Owned(self.header.clone().into_owned()),
+            lumps: core::array::from_fn(|i| {
+                let cell = &self.lumps[i];
+                let (metadata, data) = &*cell.lump.borrow();
+                LumpCell {
+                    lump: RefCell::new((Cow::Owned(*metadata.as_ref()), Cow::Owned(data.to_vec()))),
+                    dirty: Cell::new(cell.dirty.get()),
+                }
+            }),
+        }
+    }
+
+    /// Write the header followed by every lump in index order, each starting on a
+    /// [`LUMP_ALIGNMENT`] boundary
+    ///
+    /// The header and each lump are written with a separate `write_all` call, so wrap
+    /// unbuffered writers such as [`std::fs::File`] in a [`std::io::BufWriter`], or use
+    /// [`BspFile::write_to_vec`] and write the result once.
+    ///
+    /// Panics if any lump is currently borrowed mutably.
+    #[cfg(feature = "std")]
+    pub fn write_to_io<W>(&self, writer: W) -> std::io::Result<()>
+    where
+        W: std::io::Write,
+    {
+        self.write_to_io_aligned(writer, LUMP_ALIGNMENT)
+    }
+
+    /// Write like [`BspFile::write_to_io`], starting each lump on an `align`-byte boundary
+    ///
+    /// Gaps between lumps are filled with zeros. An `align` of 1 packs lumps back-to-back.
+    ///
+    /// Panics if `align` is zero or any lump is currently borrowed mutably.
+    #[cfg(feature = "std")]
+    pub fn write_to_io_aligned<W>(&self, writer: W, align: usize) -> std::io::Result<()>
+    where
+        W: std::io::Write,
+    {
+        let lumps: Vec<_> = self.lump_iter().collect();
+        self.write_lumps(
+            writer,
+            lumps
+                .iter()
+                .map(|(metadata, data)| (*metadata.borrow().as_ref(), data.as_ref())),
+            align,
+        )
+    }
+
+    /// Write to an async `writer` like [`BspFile::write_to_io`]
+    ///
+    /// The file is serialized with [`BspFile::write_to_vec`] before the first `.await`,
+    /// so no lump borrow is held while writing. The returned future still borrows
+    /// `self`, though, and a [`BspFile`] is `Send` but not `Sync` since its lumps live in
+    /// `RefCell`s, so the future isn't `Send`. To write from a task spawned on a
+    /// multi-threaded runtime, move the [`BspFile`] into the task, or call
+    /// [`BspFile::write_to_vec`] first and move the buffer instead.
+    ///
+    /// Panics if any lump is currently borrowed mutably.
+    #[cfg(feature = "tokio")]
+    pub async fn write_to_async<W>(&self, mut writer: W) -> std::io::Result<()>
+    where
+        W: tokio::io::AsyncWrite + Unpin,
+    {
+        let data = self.write_to_vec();
+        tokio::io::AsyncWriteExt::write_all(&mut writer, &data).await
+    }
+
+    /// Serialize like [`BspFile::write_to_slice`] into a buffer sized up front from the
+    /// header and lump lengths
+    ///
+    /// Panics if any lump is currently borrowed mutably.
+    pub fn write_to_vec(&self) -> Vec<u8> {
+        // Padding adds less than `LUMP_ALIGNMENT` bytes before each lump
+        let padding = N * (LUMP_ALIGNMENT - 1);
+        let mut out = vec![0; size_of::<H>() + self.total_lump_bytes() + padding];
+        let len = self
+            .write_to_slice(&mut out)
+            .expect("buffer fits the padded layout");
+        out.truncate(len);
+        out
+    }
+
+    /// Write the header followed by every lump in index order into the start of `buf`,
+    /// each starting on a [`LUMP_ALIGNMENT`] boundary, and return the number of bytes
+    /// written
+    ///
+    /// This lays out the file like [`BspFile::write_to_io`] without allocating or needing
+    /// `std`. Gaps between lumps are zeroed, and nothing is written if `buf` is too
+    /// short.
+    ///
+    /// Panics if any lump is currently borrowed mutably.
+    pub fn write_to_slice(&self, buf: &mut [u8]) -> Result<usize, WriteError> {
+        // Borrowed into an array rather than a `Vec` so that nothing is allocated
+        let lumps: [_; N] = core::array::from_fn(|i| self.lumps[i].get());
+        let lumps = lumps
+            .iter()
+            .map(|(metadata, data)| (*metadata.borrow().as_ref(), data.as_ref()));
+        let header = self.packed_header(lumps.clone(), LUMP_ALIGNMENT);
+
+        // Header, lump lengths and the padding before each lump
+        let needed = lumps
+            .clone()
+            .zip(header.directory())
+            .filter(|((_, data), _)| !data.is_empty())
+            .map(|((_, data), def)| def.offset() as usize + data.len())
+            .fold(size_of::<H>(), usize::max);
+        let out = buf
+            .get_mut(..needed)
+            .ok_or(WriteError::BufferTooSmall { needed })?;
+
+        out.fill(0);
+        out[..size_of::<H>()].copy_from_slice(header.as_bytes());
+        for (index, ((_, data), def)) in lumps.zip(header.directory()).enumerate() {
+            if !data.is_empty() {
+                let offset = def.offset() as usize;
+                let out = &mut out[offset..offset + data.len()];
+                out.copy_from_slice(data);
+                self.relocate_lump(index, out, def.offset());
+            }
+        }
+        Ok(needed)
+    }
+
+    /// Write to `writer`, keeping untouched lumps at the offsets they were parsed from
+    ///
+    /// A lump is untouched unless it was borrowed mutably (see [`Bsp::is_dirty`]). Every
+    /// other lump is relocated after the last untouched lump, aligned to [`LUMP_ALIGNMENT`]. Gaps
+    /// between lumps are filled with zeros.
+    #[cfg(feature = "std")]
+    pub fn write_to_io_preserving<W>(&self, mut writer: W) -> std::io::Result<()>
     where
         W: std::io::Write,
     {
-        const HEADER_SIZE: usize = size_of::<Header>();
+        let (header, end) = self.preserving_header();
+        let lumps: Vec<_> = self.lump_iter().collect();
+
+        let mut out = vec![0; end];
+        out[..size_of::<H>()].copy_from_slice(header.as_bytes());
+        for (index, ((_, data), def)) in lumps.iter().zip(header.directory()).enumerate() {
+            if !data.is_empty() {
+                let offset = def.offset() as usize;
+                let out = &mut out[offset..offset + data.len()];
+                out.copy_from_slice(data);
+                self.relocate_lump(index, out, def.offset());
+            }
+        }
+        writer.write_all(&out)
+    }
+
+    /// Header and file length that [`BspFile::write_to_io_preserving`] would write
+    fn preserving_header(&self) -> (H, usize) {
+        let mut header = self.header.clone().into_owned();
+
+        // Relocated lumps go after the end of the furthest lump that stays put
+        let mut end = header
+            .directory()
+            .iter()
+            .zip(&self.lumps)
+            .filter(|(def, cell)| !cell.dirty.get() && def.length() != 0)
+            .map(|(def, _)| def.offset() as usize + def.length() as usize)
+            .fold(size_of::<H>(), usize::max);
+
+        for (cell, def) in self.lumps.iter().zip(header.directory_mut()) {
+            let (metadata, data) = cell.get();
+            def.set_metadata(*metadata.as_ref());
+            if !cell.dirty.get() {
+                continue;
+            }
+
+            // Empty lumps don't extend the file, or trailing ones would pad it
+            let offset = end.next_multiple_of(LUMP_ALIGNMENT);
+            def.set_range(offset as u32, data.len() as u32);
+            if !data.is_empty() {
+                end = offset + data.len();
+            }
+        }
+        (header, end)
+    }
+
+    /// Detach every lump and mark it dirty, so that nothing is kept at its parsed offset
+    /// anymore, and return how many bytes that saves
+    ///
+    /// Afterwards [`BspFile::write_to_io_preserving`] packs lumps back-to-back like
+    /// [`BspFile::write_to_io`], dropping any gaps and dead space left between them, like
+    /// `bspzip -repack`. The savings are the difference between the two layouts as they
+    /// were before the call, or zero if packing doesn't make the file smaller.
+    pub fn repack(&mut self) -> usize {
+        let (_, preserved) = self.preserving_header();
+        let packed = self
+            .lump_sizes()
+            .into_iter()
+            .filter(|&len| len != 0)
+            .fold(size_of::<H>(), |end, len| {
+                end.next_multiple_of(LUMP_ALIGNMENT) + len
+            });
+
+        self.detach();
+        for cell in &self.lumps {
+            cell.dirty.set(true);
+        }
+        preserved.saturating_sub(packed)
+    }
+
+    /// Write the header followed by `lumps` in index order, each starting on an
+    /// `align`-byte boundary
+    #[cfg(feature = "std")]
+    pub(crate) fn write_lumps<'d, W, L>(
+        &self,
+        mut writer: W,
+        lumps: L,
+        align: usize,
+    ) -> std::io::Result<()>
+    where
+        W: std::io::Write,
+        L: Iterator<Item = (LumpMetadata, &'d [u8])> + Clone,
+    {
+        let header = self.packed_header(lumps.clone(), align);
+
+        // Write data to writer, padding up to each lump's offset
+        writer.write_all(header.as_bytes())?;
+        let mut end = size_of::<H>();
+        for (index, ((_, data), def)) in lumps.zip(header.directory()).enumerate() {
+            if data.is_empty() {
+                continue;
+            }
+            let offset = def.offset() as usize;
+            std::io::copy(
+                &mut std::io::Read::take(std::io::repeat(0), (offset - end) as u64),
+                &mut writer,
+            )?;
+            if self.lump_shift(index, def.offset()) == 0 {
+                writer.write_all(data)?;
+            } else {
+                let mut data = data.to_vec();
+                self.relocate_lump(index, &mut data, def.offset());
+                writer.write_all(&data)?;
+            }
+            end = offset + data.len();
+        }
+        Ok(())
+    }
+
+    /// How far the lump at `index` moves when written at `offset`, if its data holds
+    /// absolute offsets that have to follow it, or zero
+    fn lump_shift(&self, index: usize, offset: u32) -> i32 {
+        if H::GAME_LUMP != Some(index) {
+            return 0;
+        }
+        offset.wrapping_sub(self.header.directory()[index].offset()) as i32
+    }
+
+    /// Fix up the absolute offsets in `data` of the lump at `index`, which is being
+    /// written at `offset` instead of where the header puts it
+    ///
+    /// That's only the game lump directory, every other lump is left as is.
+    fn relocate_lump(&self, index: usize, data: &mut [u8], offset: u32) {
+        game_lump::shift_offsets(data, self.lump_shift(index, offset));
+    }
+
+    /// Header describing `lumps` laid out in index order, each starting on an
+    /// `align`-byte boundary
+    fn packed_header<'d, L>(&self, lumps: L, align: usize) -> H
+    where
+        L: Iterator<Item = (LumpMetadata, &'d [u8])>,
+    {
         let mut header = self.header.clone().into_owned();
 
         // Update lump definitions
-        let _ = self.lump_iter().zip(header.lump_defs.iter_mut()).fold(
-            // Start at offset HEADER_SIZE
-            HEADER_SIZE,
+        let _ = lumps.zip(header.directory_mut().iter_mut()).fold(
+            // Start right after the header
+            size_of::<H>(),
             |acc, ((metadata, data), def)| {
-                def.offset = acc as u32;
-                def.length = data.borrow().len() as u32;
-                def.metadata = *metadata.borrow().as_ref();
+                let offset = acc.next_multiple_of(align);
+                def.set_range(offset as u32, data.len() as u32);
+                def.set_metadata(metadata);
 
-                def.offset as usize + def.length as usize
+                offset + data.len()
             },
         );
+        header
+    }
 
-        // Write data to writer
-        writer.write_all(header.as_bytes())?;
-        for lump in &self.lumps {
-            let cell = lump.borrow();
-            writer.write_all(&cell.1)?;
+    /// Definition of the lump at `index` as [`BspFile::write_to_io`] would write it, or
+    /// `None` if it's out of range
+    ///
+    /// The offset and length reflect the current lump data laid out from scratch, not the
+    /// offsets the file was parsed from.
+    pub fn lump_def<I>(&self, index: I) -> Option<H::LumpDef>
+    where
+        I: Into<usize>,
+    {
+        let index = index.into();
+        if index >= N {
+            return None;
         }
-        Ok(())
+
+        let lumps: Vec<_> = self.lump_iter().collect();
+        let header = self.packed_header(
+            lumps
+                .iter()
+                .map(|(metadata, data)| (*metadata.borrow().as_ref(), data.as_ref())),
+            LUMP_ALIGNMENT,
+        );
+        Some(header.directory()[index])
+    }
+
+    /// Lump definitions as they were parsed from the file
+    ///
+    /// These are the file's original offsets and lengths, which edits don't change. See
+    /// [`BspFile::lump_def`] for what [`BspFile::write_to_io`] would write instead.
+    pub fn original_lump_defs(&self) -> &[H::LumpDef; N] {
+        self.header.directory()
     }
 
-    pub fn lump_cast<T, I>(&self, index: I) -> Result<Ref<'_, T>, CastError<(), T>>
+    /// Absolute byte range the lump at `index` occupied in the parsed file, or `None` if
+    /// it was empty or `index` is out of range
+    ///
+    /// Like [`BspFile::original_lump_defs`], this describes the source file and isn't
+    /// affected by edits.
+    pub fn lump_range<I>(&self, index: I) -> Option<Range<usize>>
+    where
+        I: Into<usize>,
+    {
+        let def = self.header.directory().get(index.into())?;
+        let (offset, length) = (def.offset() as usize, def.length() as usize);
+        (length != 0).then(|| offset..offset + length)
+    }
+
+    /// Indices of every lump sorted by their offset in the parsed file
+    ///
+    /// Lumps sharing an offset, such as empty ones, stay in index order.
+    pub fn physical_order(&self) -> Vec<usize> {
+        let directory = self.header.directory();
+        let mut order: Vec<_> = (0..N).collect();
+        order.sort_by_key(|&index| directory[index].offset());
+        order
+    }
+
+    pub fn lump_cast<T, I>(&self, index: I) -> Result<Ref<'_, T>, LumpCastError<T>>
     where
         T: ?Sized + FromBytes + KnownLayout + Immutable,
         I: Into<usize>,
     {
-        let cell = self.lump_cell(index);
-        let mut err = MaybeUninit::uninit();
-        Ref::filter_map(cell.borrow(), |v| match T::ref_from_bytes(&v.1) {
-            Ok(o) => Some(o),
-            Err(e) => {
-                // TODO: we sadly throw away information from the error here since
-                // this wouldn't work otherwise. It would be nice to see if this can
-                // be solved in the future.
-                //
-                // If we can't resolve this, then properly document it and use a self-describing
-                // type for the src such as `OmittedSrc`
-                err.write(e.map_src(|_| ()));
-                None
+        let index = index.into();
+        self.lump_cell(index).cast(index)
+    }
+
+    /// Cast the lump at `index` to a slice of `T`
+    ///
+    /// Shorthand for `lump_cast::<[T], _>`. A [`LumpCastErrorKind::Size`] error means the
+    /// length isn't a multiple of `size_of::<T>()`.
+    pub fn lump_cast_slice<T, I>(&self, index: I) -> Result<Ref<'_, [T]>, LumpCastError<[T]>>
+    where
+        T: FromBytes + KnownLayout + Immutable,
+        I: Into<usize>,
+    {
+        self.lump_cast(index)
+    }
+
+    /// Cast the lump at `index` to exactly one `T`
+    ///
+    /// Shorthand for `lump_cast::<T, _>` that can't be given a slice type. A
+    /// [`LumpCastErrorKind::Size`] error means the length isn't exactly `size_of::<T>()`,
+    /// so trailing bytes are reported rather than ignored.
+    pub fn lump_one<T, I>(&self, index: I) -> Result<Ref<'_, T>, LumpCastError<T>>
+    where
+        T: FromBytes + KnownLayout + Immutable,
+        I: Into<usize>,
+    {
+        self.lump_cast(index)
+    }
+
+    /// Cast the lump at `index` to a slice of any number of `T`
+    ///
+    /// Same as [`Bsp::lump_cast_slice`], named to pair with [`Bsp::lump_one`].
+    pub fn lump_many<T, I>(&self, index: I) -> Result<Ref<'_, [T]>, LumpCastError<[T]>>
+    where
+        T: FromBytes + KnownLayout + Immutable,
+        I: Into<usize>,
+    {
+        self.lump_cast(index)
+    }
+
+    /// Cast the lump at `index` to a slice of `T`, handling trailing bytes per `policy`
+    ///
+    /// [`TrailingPolicy::Strict`] behaves like [`Bsp::lump_many`].
+    pub fn lump_many_with<T, I>(
+        &self,
+        index: I,
+        policy: TrailingPolicy,
+    ) -> Result<Ref<'_, [T]>, LumpCastError<[T]>>
+    where
+        T: FromBytes + KnownLayout + Immutable,
+        I: Into<usize>,
+    {
+        let index = index.into();
+        match policy {
+            TrailingPolicy::Strict => self.lump_cast(index),
+            TrailingPolicy::IgnoreRemainder => {
+                let data = self.raw(index);
+                let len = data.len();
+                let mut kind = LumpCastErrorKind::Size;
+                Ref::filter_map(data, |data| cast_prefix(data).map_err(|e| kind = e).ok())
+                    .map_err(|_| LumpCastError::new(index, len, kind))
             }
-        })
-        // SAFETY: if we're Err(_) then `err` will be initialized
-        .map_err(|_| unsafe { err.assume_init() })
+        }
     }
 
-    pub fn lump_cast_mut<T, I>(&self, index: I) -> Result<RefMut<'_, T>, CastError<(), T>>
+    /// Cast the lump at `index` to `T` mutably, marking it dirty
+    ///
+    /// Data that still borrows from the parsed buffer is copied into its own allocation
+    /// the first time, since the buffer is immutable. Later casts reuse that copy, so the
+    /// only per-call cost is the cast itself: hold on to the returned guard in hot loops
+    /// rather than casting per edit, or call [`Bsp::make_lump_mut`] up front.
+    pub fn lump_cast_mut<T, I>(&self, index: I) -> Result<RefMut<'_, T>, LumpCastError<T>>
     where
         T: ?Sized + FromBytes + IntoBytes + KnownLayout + Immutable,
         I: Into<usize>,
     {
-        let cell = self.lump_cell(index);
-        let mut err = MaybeUninit::uninit();
-        RefMut::filter_map(cell.borrow_mut(), |v| {
-            match T::mut_from_bytes(v.1.to_mut()) {
-                Ok(o) => Some(o),
-                Err(e) => {
-                    // TODO: we sadly throw away information from the error here since
-                    // this wouldn't work otherwise. It would be nice to see if this can
-                    // be solved in the future.
-                    //
-                    // If we can't resolve this, then properly document it and use a self-describing
-                    // type for the src such as `OmittedSrc`
-                    err.write(e.map_src(|_| ()));
-                    None
-                }
-            }
-        })
-        // SAFETY: if we're Err(_) then `err` will be initialized
-        .map_err(|_| unsafe { err.assume_init() })
+        let index = index.into();
+        self.lump_cell(index).cast_mut(index)
     }
 
     pub fn lump<I>(&self, index: I) -> LumpRef<'a, '_>
     where
         I: Into<usize>,
     {
-        let cell = self.lump_cell(index);
-        Ref::map_split(cell.borrow(), |v| (&v.0, &v.1))
+        self.lump_cell(index).get()
     }
 
     pub fn lump_mut<I>(&self, index: I) -> LumpRefMut<'a, '_>
     where
         I: Into<usize>,
     {
-        let cell = self.lump_cell(index);
-        RefMut::map_split(cell.borrow_mut(), |v| (&mut v.0, &mut v.1))
+        self.lump_cell(index).get_mut()
+    }
+
+    /// Data of the lump at `index`, without its metadata
+    ///
+    /// This stands in for `Index`, which can't hand out the [`Ref`] guarding the lump.
+    pub fn raw<I>(&self, index: I) -> Ref<'_, [u8]>
+    where
+        I: Into<usize>,
+    {
+        Ref::map(self.lump_cell(index).lump.borrow(), |(_, data)| &**data)
+    }
+
+    /// Data of the lump at `index` as text, such as [`LumpId::Entities`]
+    ///
+    /// A single trailing null byte is trimmed, as written by the compiler after text
+    /// lumps. Any other null byte is kept.
+    pub fn lump_as_str<I>(&self, index: I) -> Result<Ref<'_, str>, core::str::Utf8Error>
+    where
+        I: Into<usize>,
+    {
+        let mut error = None;
+        Ref::filter_map(self.lump_cell(index).lump.borrow(), |(_, data)| {
+            let text = data.strip_suffix(&[0]).unwrap_or(data);
+            core::str::from_utf8(text).map_err(|e| error = Some(e)).ok()
+        })
+        .map_err(|_| error.expect("set on failure"))
+    }
+
+    /// Like [`Bsp::lump_cast`], but returns `None` if `index` is out of range
+    pub fn try_lump_cast<T, I>(&self, index: I) -> Option<Result<Ref<'_, T>, LumpCastError<T>>>
+    where
+        T: ?Sized + FromBytes + KnownLayout + Immutable,
+        I: Into<usize>,
+    {
+        let index = index.into();
+        self.get_lump_cell(index).map(|cell| cell.cast(index))
+    }
+
+    /// Like [`Bsp::lump_cast_mut`], but returns `None` if `index` is out of range
+    pub fn try_lump_cast_mut<T, I>(
+        &self,
+        index: I,
+    ) -> Option<Result<RefMut<'_, T>, LumpCastError<T>>>
+    where
+        T: ?Sized + FromBytes + IntoBytes + KnownLayout + Immutable,
+        I: Into<usize>,
+    {
+        let index = index.into();
+        self.get_lump_cell(index).map(|cell| cell.cast_mut(index))
+    }
+
+    /// Like [`Bsp::lump`], but returns `None` if `index` is out of range
+    pub fn try_lump<I>(&self, index: I) -> Option<LumpRef<'a, '_>>
+    where
+        I: Into<usize>,
+    {
+        self.get_lump_cell(index).map(LumpCell::get)
+    }
+
+    /// Like [`Bsp::lump_mut`], but returns `None` if `index` is out of range
+    pub fn try_lump_mut<I>(&self, index: I) -> Option<LumpRefMut<'a, '_>>
+    where
+        I: Into<usize>,
+    {
+        self.get_lump_cell(index).map(LumpCell::get_mut)
+    }
+
+    /// Whether the lump at `index` was borrowed mutably through [`Bsp::lump_mut`] or
+    /// [`Bsp::lump_cast_mut`]
+    ///
+    /// Handing out mutable access is enough to mark a lump dirty, even if it ends up
+    /// unchanged.
+    pub fn is_dirty<I>(&self, index: I) -> bool
+    where
+        I: Into<usize>,
+    {
+        self.lump_cell(index).dirty.get()
+    }
+
+    /// Copy the data of the lump at `index` into its own allocation if it still borrows
+    /// from the parsed buffer
+    ///
+    /// This is [`BspFile::detach`] for a single lump, and doesn't mark it dirty since the
+    /// data is unchanged. Owned data is left as is.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lump is currently borrowed.
+    pub fn make_lump_mut<I>(&self, index: I)
+    where
+        I: Into<usize>,
+    {
+        self.lump_cell(index).lump.borrow_mut().1.to_mut();
+    }
+
+    /// Whether the data of the lump at `index` still borrows from the parsed buffer
+    ///
+    /// Data becomes owned once it's replaced, written to or passed to
+    /// [`Bsp::make_lump_mut`], which mutable access alone doesn't do, so this can differ
+    /// from [`Bsp::is_dirty`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lump is currently borrowed mutably.
+    pub fn is_lump_borrowed<I>(&self, index: I) -> bool
+    where
+        I: Into<usize>,
+    {
+        matches!(self.lump_cell(index).lump.borrow().1, Cow::Borrowed(_))
+    }
+
+    /// Whether the metadata of the lump at `index` still borrows from the parsed header
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lump is currently borrowed mutably.
+    pub fn is_lump_metadata_borrowed<I>(&self, index: I) -> bool
+    where
+        I: Into<usize>,
+    {
+        matches!(self.lump_cell(index).lump.borrow().0, Cow::Borrowed(_))
     }
 
     fn lump_cell<I>(&self, index: I) -> &LumpCell<'a>
@@ -193,22 +1291,192 @@ impl<'a> Bsp<'a> {
         I: Into<usize>,
     {
         let index: usize = index.into();
-        assert!(index < LUMP_DEF_COUNT);
+        assert!(index < N);
 
         &self.lumps[index]
     }
 
-    fn lump_iter(
-        &self,
-    ) -> impl Iterator<Item = (Ref<'_, Cow<'a, LumpMetadata>>, Ref<'_, Cow<'a, [u8]>>)> {
+    /// Lump cell at `index`, or `None` if it's out of range
+    fn get_lump_cell<I>(&self, index: I) -> Option<&LumpCell<'a>>
+    where
+        I: Into<usize>,
+    {
+        self.lumps.get(index.into())
+    }
+
+    /// Length of the data of the lump at `index`, or `None` if it's out of range
+    pub fn lump_len<I>(&self, index: I) -> Option<usize>
+    where
+        I: Into<usize>,
+    {
+        Some(self.get_lump_cell(index)?.lump.borrow().1.len())
+    }
+
+    /// Length of the data of every lump
+    pub fn lump_sizes(&self) -> [usize; N] {
+        core::array::from_fn(|i| self.lumps[i].lump.borrow().1.len())
+    }
+
+    /// Number of lumps that contain data
+    pub fn lump_count_nonempty(&self) -> usize {
+        self.lump_sizes().iter().filter(|&&len| len != 0).count()
+    }
+
+    /// Combined length of the data of every lump
+    pub fn total_lump_bytes(&self) -> usize {
+        self.lump_sizes().iter().sum()
+    }
+
+    /// Version of the lump at `index`, or `None` if it's out of range
+    pub fn lump_version<I>(&self, index: I) -> Option<u32>
+    where
+        I: Into<usize>,
+    {
+        Some(self.get_lump_cell(index)?.lump.borrow().0.version)
+    }
+
+    /// Identifier of the lump at `index`, or `None` if it's out of range
+    pub fn lump_fourcc<I>(&self, index: I) -> Option<[u8; 4]>
+    where
+        I: Into<usize>,
+    {
+        Some(self.get_lump_cell(index)?.lump.borrow().0.identifier)
+    }
+
+    /// Replace the metadata and data of the lump at `index`, marking it dirty
+    pub fn replace_lump<I>(&self, index: I, metadata: LumpMetadata, data: Vec<u8>)
+    where
+        I: Into<usize>,
+    {
+        let (mut old_metadata, mut old_data) = self.lump_mut(index);
+        *old_metadata = Cow::Owned(metadata);
+        *old_data = Cow::Owned(data);
+    }
+
+    /// Empty the data of the lump at `index`, keeping its metadata and marking it dirty
+    pub fn clear_lump<I>(&self, index: I)
+    where
+        I: Into<usize>,
+    {
+        *self.lump_mut(index).1 = Cow::Owned(Vec::new());
+    }
+
+    /// Set the metadata of the lump at `index` without touching its data
+    ///
+    /// Offsets and lengths are recomputed on write, so only metadata can be set. The lump
+    /// isn't marked dirty (see [`BspFile::is_dirty`]).
+    pub fn set_lump_metadata<I>(&self, index: I, metadata: LumpMetadata)
+    where
+        I: Into<usize>,
+    {
+        self.lump_cell(index).lump.borrow_mut().0 = Cow::Owned(metadata);
+    }
+
+    /// Set the version of the lump at `index` without touching its data
+    ///
+    /// Only metadata changes, so the lump isn't marked dirty (see [`BspFile::is_dirty`]).
+    pub fn set_lump_version<I>(&self, index: I, version: u32)
+    where
+        I: Into<usize>,
+    {
+        self.lump_cell(index).lump.borrow_mut().0.to_mut().version = version;
+    }
+
+    /// Set the identifier of the lump at `index` without touching its data
+    ///
+    /// Only metadata changes, so the lump isn't marked dirty (see [`BspFile::is_dirty`]).
+    pub fn set_lump_fourcc<I>(&self, index: I, fourcc: [u8; 4])
+    where
+        I: Into<usize>,
+    {
+        self.lump_cell(index)
+            .lump
+            .borrow_mut()
+            .0
+            .to_mut()
+            .identifier = fourcc;
+    }
+
+    /// Iterate over every lump with its index, metadata and data
+    ///
+    /// # Panics
+    ///
+    /// Panics when reaching a lump that is currently mutably borrowed.
+    pub fn lumps_iter(&self) -> impl Iterator<Item = (usize, LumpRef<'a, '_>)> {
+        self.lump_iter().enumerate()
+    }
+
+    pub(crate) fn lump_iter(&self) -> impl Iterator<Item = LumpRef<'a, '_>> {
+        self.lumps.iter().map(LumpCell::get)
+    }
+}
+
+impl<'b, 'a, H, const N: usize> IntoIterator for &'b BspFile<'a, H, N>
+where
+    H: BspHeader<N>,
+{
+    type Item = (usize, LumpRef<'a, 'b>);
+    type IntoIter = NonEmptyLumps<'a, 'b>;
+
+    /// Iterate over the lumps that contain data, see [`BspFile::lumps_iter`] for all of them
+    fn into_iter(self) -> Self::IntoIter {
+        NonEmptyLumps {
+            lumps: self.lumps.iter().enumerate(),
+        }
+    }
+}
+
+/// Iterator over the lumps of a [`BspFile`] that contain data, with their index
+///
+/// # Panics
+///
+/// Panics when reaching a lump that is currently mutably borrowed.
+pub struct NonEmptyLumps<'a, 'b> {
+    lumps: core::iter::Enumerate<core::slice::Iter<'b, LumpCell<'a>>>,
+}
+
+impl<'a, 'b> Iterator for NonEmptyLumps<'a, 'b> {
+    type Item = (usize, LumpRef<'a, 'b>);
+
+    fn next(&mut self) -> Option<Self::Item> {
         self.lumps
-            .iter()
-            .map(|v| Ref::map_split(v.borrow(), |e| (&e.0, &e.1)))
+            .by_ref()
+            .map(|(index, cell)| (index, cell.get()))
+            .find(|(_, (_, data))| !data.is_empty())
     }
 }
 
-impl std::fmt::Debug for Bsp<'_> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+/// Deep copy that owns all of its data, like [`BspFile::to_owned`]
+///
+/// The clone keeps the original lifetime so that it can stand in for it; use
+/// [`BspFile::to_owned`] to get a `'static` copy instead. Dirty flags are kept.
+///
+/// Panics if any lump is currently borrowed mutably.
+impl<H, const N: usize> Clone for BspFile<'_, H, N>
+where
+    H: BspHeader<N>,
+{
+    fn clone(&self) -> Self {
+        self.deep_copy()
+    }
+}
+
+impl Bsp<'_> {
+    /// Revision of the map, which compilers increment on every save
+    pub fn map_revision(&self) -> i32 {
+        self.header.revision
+    }
+
+    /// Set the revision of the map, copying the header if it's still borrowed
+    ///
+    /// Unlike lump edits this needs exclusive access, since the header isn't kept in a cell.
+    pub fn set_map_revision(&mut self, revision: i32) {
+        self.header.to_mut().revision = revision;
+    }
+}
+
+impl core::fmt::Debug for Bsp<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("Bsp")
             .field("identifier", &self.header.identifier)
             .field("version", &self.header.version)
@@ -217,3 +1485,239 @@ impl std::fmt::Debug for Bsp<'_> {
             .finish_non_exhaustive()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Header-only file with the given `(index, offset, length)` lump definitions,
+    /// followed by `extra` zero bytes
+    fn file_with_defs(defs: &[(usize, u32, u32)], extra: usize) -> Vec<u8> {
+        let mut header = Header::new_zeroed();
+        header.identifier = *b"VBSP";
+        header.version = 20;
+        for &(index, offset, length) in defs {
+            header.lump_defs[index].offset = offset;
+            header.lump_defs[index].length = length;
+        }
+        let mut file = header.as_bytes().to_vec();
+        file.resize(file.len() + extra, 0);
+        file
+    }
+
+    fn parse_error(file: &[u8]) -> ParseError {
+        Bsp::parse(file).expect_err("file should be rejected")
+    }
+
+    #[test]
+    fn offset_inside_header() {
+        let file = file_with_defs(&[(3, 100, 4)], 16);
+        assert!(matches!(
+            parse_error(&file),
+            ParseError::OffsetInsideHeader { index: 3 }
+        ));
+    }
+
+    // `offset + length` of two `u32`s only overflows a 32-bit `usize`
+    #[cfg(target_pointer_width = "32")]
+    #[test]
+    fn offset_overflow() {
+        let file = file_with_defs(&[(7, u32::MAX, u32::MAX)], 0);
+        assert!(matches!(
+            parse_error(&file),
+            ParseError::OffsetOverflow { index: 7 }
+        ));
+    }
+
+    #[cfg(not(target_pointer_width = "32"))]
+    #[test]
+    fn offset_overflow() {
+        let file = file_with_defs(&[(7, u32::MAX, u32::MAX)], 0);
+        assert!(matches!(
+            parse_error(&file),
+            ParseError::LumpOutOfBounds { index: 7, .. }
+        ));
+    }
+
+    #[test]
+    fn lump_past_end_of_file() {
+        let header_size = size_of::<Header>() as u32;
+        let file = file_with_defs(&[(0, header_size, 4), (5, header_size + 4, 8)], 8);
+        match parse_error(&file) {
+            ParseError::LumpOutOfBounds {
+                index,
+                offset,
+                length,
+                file_len,
+            } => {
+                assert_eq!(index, 5);
+                assert_eq!(offset, header_size as usize + 4);
+                assert_eq!(length, 8);
+                assert_eq!(file_len, file.len());
+            }
+            e => panic!("unexpected error {e:?}"),
+        }
+    }
+
+    #[test]
+    fn empty_lump_with_bogus_offset() {
+        let file = file_with_defs(&[(2, 12, 0), (9, u32::MAX, 0)], 0);
+        let bsp = Bsp::parse(&file).unwrap();
+        assert!(bsp.raw(2usize).is_empty());
+        assert!(bsp.raw(9usize).is_empty());
+    }
+
+    fn metadata(version: u32) -> LumpMetadata {
+        LumpMetadata {
+            version,
+            identifier: [0; 4],
+        }
+    }
+
+    /// Written map with a few lumps of lengths that need padding
+    #[cfg(feature = "std")]
+    fn synthetic_file() -> Vec<u8> {
+        BspBuilder::new(*b"VBSP", 20)
+            .revision(3)
+            .set_lump(LumpId::Entities, metadata(0), b"{\n}\n\0".to_vec())
+            .set_lump(LumpId::Planes, metadata(0), vec![1; 20])
+            .set_lump(LumpId::Vertexes, metadata(0), vec![2; 36])
+            .set_lump(LumpId::PakFile, metadata(1), vec![3; 5])
+            .build()
+            .write_to_vec()
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn round_trip_unchanged() {
+        let file = synthetic_file();
+        let bsp = Bsp::parse(&file).unwrap();
+
+        let mut written = Vec::new();
+        bsp.write_to_io(&mut written).unwrap();
+        let reparsed = Bsp::parse(&written).unwrap();
+        for index in 0..LUMP_DEF_COUNT {
+            assert_eq!(*bsp.raw(index), *reparsed.raw(index), "lump {index}");
+            assert_eq!(bsp.lump_version(index), reparsed.lump_version(index));
+        }
+        assert_eq!(reparsed.map_revision(), 3);
+
+        let mut preserved = Vec::new();
+        bsp.write_to_io_preserving(&mut preserved).unwrap();
+        assert_eq!(preserved, file);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn preserving_moves_only_edited_lump() {
+        let file = synthetic_file();
+        let bsp = Bsp::parse(&file).unwrap();
+        bsp.lump_mut(LumpId::Planes).1.to_mut().extend([4; 20]);
+
+        let mut written = Vec::new();
+        bsp.write_to_io_preserving(&mut written).unwrap();
+        let reparsed = Bsp::parse(&written).unwrap();
+        assert_eq!(*reparsed.raw(LumpId::Planes), *bsp.raw(LumpId::Planes));
+
+        let before = bsp.original_lump_defs();
+        let after = reparsed.original_lump_defs();
+        for index in 0..LUMP_DEF_COUNT {
+            let same = before[index].offset() == after[index].offset()
+                && before[index].length() == after[index].length();
+            assert_eq!(same, index != usize::from(LumpId::Planes), "lump {index}");
+        }
+    }
+
+    #[test]
+    fn cast_rejects_partial_record() {
+        let bsp = BspBuilder::new(*b"VBSP", 20)
+            .set_lump(LumpId::Vertexes, metadata(0), vec![0; 13])
+            .build();
+
+        let error = bsp.vertices().unwrap_err();
+        assert_eq!(error.kind, LumpCastErrorKind::Size);
+        assert_eq!(error.index, usize::from(LumpId::Vertexes));
+        assert_eq!(error.len, 13);
+        assert_eq!(error.element_size(), size_of::<Vertex>());
+
+        // Neither the slice cast nor its strict variant drop the extra byte
+        assert!(bsp.lump_many::<Vertex, _>(LumpId::Vertexes).is_err());
+        assert!(bsp
+            .lump_many_with::<Vertex, _>(LumpId::Vertexes, TrailingPolicy::Strict)
+            .is_err());
+    }
+
+    #[test]
+    fn face_cast_rejects_partial_record() {
+        assert_eq!(size_of::<Face>(), 56);
+
+        // One byte short, one byte over, and a whole number of words but not of faces
+        for len in [55, 57, 60, 111] {
+            let bsp = BspBuilder::new(*b"VBSP", 20)
+                .set_lump(LumpId::Faces, metadata(1), vec![0; len])
+                .build();
+
+            let error = bsp.faces().unwrap_err();
+            assert_eq!(error.kind, LumpCastErrorKind::Size, "length {len}");
+            assert_eq!(error.index, usize::from(LumpId::Faces));
+            assert_eq!(error.len, len);
+            assert_eq!(error.element_size(), size_of::<Face>());
+
+            let error = bsp
+                .lump_many_with::<Face, _>(LumpId::Faces, TrailingPolicy::Strict)
+                .unwrap_err();
+            assert_eq!(error.kind, LumpCastErrorKind::Size, "length {len}");
+        }
+
+        let bsp = BspBuilder::new(*b"VBSP", 20)
+            .set_lump(LumpId::Faces, metadata(1), vec![0; 112])
+            .build();
+        assert_eq!(bsp.faces().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn header_only_round_trip() {
+        let bsp = BspBuilder::new(*b"VBSP", 20).revision(7).build();
+        let header_size = size_of::<Header>();
+
+        let mut buf = vec![0xaa; header_size + 16];
+        assert_eq!(
+            bsp.write_to_slice(&mut buf[..header_size - 1]),
+            Err(WriteError::BufferTooSmall {
+                needed: header_size
+            })
+        );
+        assert_eq!(bsp.write_to_slice(&mut buf), Ok(header_size));
+
+        let written = bsp.write_to_vec();
+        assert_eq!(written, buf[..header_size]);
+
+        for file in [&buf[..header_size], &written] {
+            let reparsed = Bsp::parse(file).unwrap();
+            assert_eq!(reparsed.map_revision(), 7);
+            assert_eq!(reparsed.total_lump_bytes(), 0);
+            assert_eq!(reparsed.lump_count_nonempty(), 0);
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn write_to_async_matches_write_to_vec() {
+        use core::{future::Future, pin::pin, task};
+
+        let file = synthetic_file();
+        let bsp = Bsp::parse(&file).unwrap();
+
+        // Writing to a `Vec` never waits, so a single poll finishes the future
+        let mut written = Vec::new();
+        {
+            let future = pin!(bsp.write_to_async(&mut written));
+            let mut context = task::Context::from_waker(task::Waker::noop());
+            match future.poll(&mut context) {
+                task::Poll::Ready(result) => result.unwrap(),
+                task::Poll::Pending => panic!("writing to a Vec shouldn't wait"),
+            }
+        }
+        assert_eq!(written, bsp.write_to_vec());
+    }
+}
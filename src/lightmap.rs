@@ -0,0 +1,104 @@
+use alloc::vec::Vec;
+
+use zerocopy_derive::*;
+
+use crate::{Bsp, Face, LumpId, SurfaceFlags};
+
+/// Style slot marking the end of a face's light styles
+const NO_STYLE: u8 = 255;
+
+/// Lightmap luxel in shared-exponent RGB (`ColorRGBExp32`)
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub struct ColorRgbExp32 {
+    /// Red mantissa
+    pub r: u8,
+    /// Green mantissa
+    pub g: u8,
+    /// Blue mantissa
+    pub b: u8,
+    /// Power of two shared by all channels
+    pub exponent: i8,
+}
+
+impl ColorRgbExp32 {
+    /// Linear RGB, where 1.0 is full brightness
+    pub fn to_linear(self) -> [f32; 3] {
+        let scale = exp2(self.exponent) / 255.0;
+        [self.r, self.g, self.b].map(|c| f32::from(c) * scale)
+    }
+}
+
+/// `2^exponent`, exactly and without `std`
+fn exp2(exponent: i8) -> f32 {
+    // Both halves are in the normal range, and their product is exact even when it's
+    // subnormal
+    let normal = |e: i32| f32::from_bits(((e + 127) as u32) << 23);
+    let exponent = i32::from(exponent);
+    normal(exponent / 2) * normal(exponent - exponent / 2)
+}
+
+/// Decoded lightmap of a single face, see [`Bsp::face_lightmap`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lightmap {
+    /// Luxels per row
+    pub width: usize,
+    /// Number of rows
+    pub height: usize,
+    /// Whether each style holds four maps (flat, then one per bump basis) instead of one
+    pub bumped: bool,
+    /// Linear RGB luxels of each light style, in row-major order
+    pub styles: Vec<Vec<[f32; 3]>>,
+}
+
+impl Bsp<'_> {
+    /// Decoded samples of `face` from [`LumpId::Lighting`]
+    ///
+    /// Returns `None` if the face has no lightmap (`lightofs` is -1), its texinfo is out of
+    /// range, or its samples lie outside the lump.
+    pub fn face_lightmap(&self, face: &Face) -> Option<Lightmap> {
+        self.lightmap(face, LumpId::Lighting)
+    }
+
+    /// Decoded samples of `face` from [`LumpId::LightingHdr`], like [`Bsp::face_lightmap`]
+    pub fn face_lightmap_hdr(&self, face: &Face) -> Option<Lightmap> {
+        self.lightmap(face, LumpId::LightingHdr)
+    }
+
+    fn lightmap(&self, face: &Face, lump: LumpId) -> Option<Lightmap> {
+        // `lightofs` is a byte offset, -1 if the face isn't lit
+        let offset = usize::try_from(face.lightofs).ok()?;
+        if offset % size_of::<ColorRgbExp32>() != 0 {
+            return None;
+        }
+        let width = usize::try_from(face.lightmap_size[0]).ok()? + 1;
+        let height = usize::try_from(face.lightmap_size[1]).ok()? + 1;
+
+        let texinfo = usize::try_from(face.texinfo).ok()?;
+        let bumped = self
+            .texinfo()
+            .ok()?
+            .get(texinfo)?
+            .surface_flags()
+            .contains(SurfaceFlags::BUMPLIGHT);
+        let maps = if bumped { 4 } else { 1 };
+
+        let styles = face.styles.iter().take_while(|&&s| s != NO_STYLE).count();
+        let per_style = width.checked_mul(height)?.checked_mul(maps)?;
+
+        let start = offset / size_of::<ColorRgbExp32>();
+        let end = start.checked_add(per_style.checked_mul(styles)?)?;
+        let samples = self.lump_cast_slice::<ColorRgbExp32, _>(lump).ok()?;
+        let samples = samples.get(start..end)?;
+
+        Some(Lightmap {
+            width,
+            height,
+            bumped,
+            styles: samples
+                .chunks(per_style)
+                .map(|style| style.iter().map(|luxel| luxel.to_linear()).collect())
+                .collect(),
+        })
+    }
+}
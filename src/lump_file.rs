@@ -0,0 +1,117 @@
+use alloc::vec::Vec;
+
+use zerocopy::{FromBytes, IntoBytes};
+use zerocopy_derive::*;
+
+use crate::{Bsp, LumpMetadata, LUMP_DEF_COUNT};
+
+/// Header of an external lump file (`lumpfileheader_t`), as stored in
+/// `<map>_l_<index>.lmp` next to the map
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Debug, Clone, Copy)]
+#[repr(C)]
+pub struct LumpFileHeader {
+    /// Offset of the lump data from the start of the lump file
+    pub lump_offset: i32,
+    /// Index of the lump this file overrides
+    pub lump_id: i32,
+    /// Lump version
+    pub lump_version: i32,
+    /// Length of the lump data
+    pub lump_length: i32,
+    /// Revision of the map the lump file was written for
+    pub map_revision: i32,
+}
+
+/// Error returned when applying an external lump file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LumpFileError {
+    /// Data is too short to contain the header
+    HeaderTooShort,
+    /// Lump index is out of range
+    InvalidLumpId(i32),
+    /// Lump data extends past the end of the lump file
+    OutOfBounds {
+        /// Offset of the lump data
+        offset: i32,
+        /// Length of the lump data
+        length: i32,
+        /// Length of the lump file
+        file_len: usize,
+    },
+}
+
+impl core::fmt::Display for LumpFileError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::HeaderTooShort => f.write_str("lump file is too short to contain a header"),
+            Self::InvalidLumpId(id) => write!(f, "lump file overrides invalid lump {id}"),
+            Self::OutOfBounds {
+                offset,
+                length,
+                file_len,
+            } => write!(
+                f,
+                "lump data at {offset}..{offset}+{length} exceeds lump file length {file_len}"
+            ),
+        }
+    }
+}
+
+impl core::error::Error for LumpFileError {}
+
+impl Bsp<'_> {
+    /// Replace the lump an external lump file (`.lmp`) overrides with its contents,
+    /// marking it dirty
+    ///
+    /// The lump takes the file's version and a zeroed identifier. The header is returned
+    /// so that callers can compare [`LumpFileHeader::map_revision`] against the map's,
+    /// which the engine expects to match.
+    pub fn apply_lump_file(&self, data: &[u8]) -> Result<LumpFileHeader, LumpFileError> {
+        let (header, _) =
+            LumpFileHeader::read_from_prefix(data).map_err(|_| LumpFileError::HeaderTooShort)?;
+
+        let index = usize::try_from(header.lump_id)
+            .ok()
+            .filter(|&index| index < LUMP_DEF_COUNT)
+            .ok_or(LumpFileError::InvalidLumpId(header.lump_id))?;
+        let lump = usize::try_from(header.lump_offset)
+            .ok()
+            .zip(usize::try_from(header.lump_length).ok())
+            .and_then(|(offset, length)| data.get(offset..offset.checked_add(length)?))
+            .ok_or(LumpFileError::OutOfBounds {
+                offset: header.lump_offset,
+                length: header.lump_length,
+                file_len: data.len(),
+            })?;
+
+        let metadata = LumpMetadata {
+            version: header.lump_version as u32,
+            identifier: [0; 4],
+        };
+        self.replace_lump(index, metadata, lump.to_vec());
+        Ok(header)
+    }
+
+    /// Serialize the lump at `index` as an external lump file (`.lmp`) for this map
+    ///
+    /// Panics if `index` is out of range or the lump is currently borrowed mutably.
+    pub fn export_lump_file<I>(&self, index: I) -> Vec<u8>
+    where
+        I: Into<usize>,
+    {
+        let index = index.into();
+        let (metadata, data) = self.lump(index);
+        let header = LumpFileHeader {
+            lump_offset: size_of::<LumpFileHeader>() as i32,
+            lump_id: index as i32,
+            lump_version: metadata.version as i32,
+            lump_length: data.len() as i32,
+            map_revision: self.header.revision,
+        };
+
+        let mut out = Vec::with_capacity(size_of::<LumpFileHeader>() + data.len());
+        out.extend_from_slice(header.as_bytes());
+        out.extend_from_slice(&data);
+        out
+    }
+}
@@ -0,0 +1,234 @@
+use crate::LUMP_DEF_COUNT;
+
+/// Source engine lump index
+///
+/// Slots marked as unused are not written by the stock Source compilers, though some
+/// branches of the engine repurpose them; consult the game's own headers for those.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(usize)]
+pub enum LumpId {
+    /// Map entities, as key-value text
+    Entities = 0,
+    /// Plane array
+    Planes = 1,
+    /// Index to texture names
+    TexData = 2,
+    /// Vertex array
+    Vertexes = 3,
+    /// Compressed visibility bit arrays
+    Visibility = 4,
+    /// BSP tree nodes
+    Nodes = 5,
+    /// Face texture array
+    TexInfo = 6,
+    /// Face array
+    Faces = 7,
+    /// Lightmap samples
+    Lighting = 8,
+    /// Occlusion polygons and vertices
+    Occlusion = 9,
+    /// BSP tree leaf nodes
+    Leafs = 10,
+    /// Correlates faces to Hammer face IDs
+    FaceIds = 11,
+    /// Edge array
+    Edges = 12,
+    /// Index of edges
+    SurfEdges = 13,
+    /// Brush models (geometry of brush entities)
+    Models = 14,
+    /// Internal world lights converted from the entity lump
+    WorldLights = 15,
+    /// Index to faces in each leaf
+    LeafFaces = 16,
+    /// Index to brushes in each leaf
+    LeafBrushes = 17,
+    /// Brush array
+    Brushes = 18,
+    /// Brushside array
+    BrushSides = 19,
+    /// Area array
+    Areas = 20,
+    /// Portals between areas
+    AreaPortals = 21,
+    /// Unused (formerly portals, prop collision in Left 4 Dead 2)
+    Unused0 = 22,
+    /// Unused (formerly clusters, prop hulls in Left 4 Dead 2)
+    Unused1 = 23,
+    /// Unused (formerly portal vertices, prop hull vertices in Left 4 Dead 2)
+    Unused2 = 24,
+    /// Unused (formerly cluster portals, prop triangles in Left 4 Dead 2)
+    Unused3 = 25,
+    /// Displacement surface array
+    DispInfo = 26,
+    /// Brush faces array before splitting
+    OriginalFaces = 27,
+    /// Displacement physics collision data
+    PhysDisp = 28,
+    /// Physics collision data
+    PhysCollide = 29,
+    /// Face plane normals
+    VertNormals = 30,
+    /// Face plane normal index array
+    VertNormalIndices = 31,
+    /// Displacement lightmap alphas (unused since version 20)
+    DispLightmapAlphas = 32,
+    /// Vertices of displacement surface meshes
+    DispVerts = 33,
+    /// Displacement lightmap sample positions
+    DispLightmapSamplePositions = 34,
+    /// Game-specific data lump
+    GameLump = 35,
+    /// Data for leaf nodes that are inside water
+    LeafWaterData = 36,
+    /// Water polygon data
+    Primitives = 37,
+    /// Water polygon vertices
+    PrimVerts = 38,
+    /// Water polygon vertex index array
+    PrimIndices = 39,
+    /// Embedded uncompressed zip-format file
+    PakFile = 40,
+    /// Clipped portal polygon vertices
+    ClipPortalVerts = 41,
+    /// `env_cubemap` location array
+    Cubemaps = 42,
+    /// Texture name data
+    TexDataStringData = 43,
+    /// Index array into texture name data
+    TexDataStringTable = 44,
+    /// `info_overlay` data array
+    Overlays = 45,
+    /// Distance from leaves to water
+    LeafMinDistToWater = 46,
+    /// Macro texture info for faces
+    FaceMacroTextureInfo = 47,
+    /// Displacement surface triangles
+    DispTris = 48,
+    /// Unused (formerly compressed physics collision surfaces, prop blob in newer branches)
+    PhysCollideSurface = 49,
+    /// `info_overlay`s on water faces
+    WaterOverlays = 50,
+    /// Index into [`LumpId::LeafAmbientLightingHdr`] (lightmap pages on Xbox)
+    LeafAmbientIndexHdr = 51,
+    /// Index into [`LumpId::LeafAmbientLighting`] (lightmap page infos on Xbox)
+    LeafAmbientIndex = 52,
+    /// HDR lightmap samples
+    LightingHdr = 53,
+    /// Internal HDR world lights converted from the entity lump
+    WorldLightsHdr = 54,
+    /// HDR per-leaf ambient light samples
+    LeafAmbientLightingHdr = 55,
+    /// LDR per-leaf ambient light samples
+    LeafAmbientLighting = 56,
+    /// Unused (formerly Xbox zip pakfile)
+    XZipPakFile = 57,
+    /// HDR maps may have different face data
+    FacesHdr = 58,
+    /// Extended level-wide flags
+    MapFlags = 59,
+    /// Fade distances for overlays
+    OverlayFades = 60,
+    /// System level settings (min/max CPU & GPU to render this overlay)
+    OverlaySystemLevels = 61,
+    /// Physics level settings
+    PhysLevel = 62,
+    /// Displacement multiblend info
+    DispMultiBlend = 63,
+}
+
+impl LumpId {
+    /// Every lump index, in order
+    pub const ALL: [Self; LUMP_DEF_COUNT] = [
+        Self::Entities,
+        Self::Planes,
+        Self::TexData,
+        Self::Vertexes,
+        Self::Visibility,
+        Self::Nodes,
+        Self::TexInfo,
+        Self::Faces,
+        Self::Lighting,
+        Self::Occlusion,
+        Self::Leafs,
+        Self::FaceIds,
+        Self::Edges,
+        Self::SurfEdges,
+        Self::Models,
+        Self::WorldLights,
+        Self::LeafFaces,
+        Self::LeafBrushes,
+        Self::Brushes,
+        Self::BrushSides,
+        Self::Areas,
+        Self::AreaPortals,
+        Self::Unused0,
+        Self::Unused1,
+        Self::Unused2,
+        Self::Unused3,
+        Self::DispInfo,
+        Self::OriginalFaces,
+        Self::PhysDisp,
+        Self::PhysCollide,
+        Self::VertNormals,
+        Self::VertNormalIndices,
+        Self::DispLightmapAlphas,
+        Self::DispVerts,
+        Self::DispLightmapSamplePositions,
+        Self::GameLump,
+        Self::LeafWaterData,
+        Self::Primitives,
+        Self::PrimVerts,
+        Self::PrimIndices,
+        Self::PakFile,
+        Self::ClipPortalVerts,
+        Self::Cubemaps,
+        Self::TexDataStringData,
+        Self::TexDataStringTable,
+        Self::Overlays,
+        Self::LeafMinDistToWater,
+        Self::FaceMacroTextureInfo,
+        Self::DispTris,
+        Self::PhysCollideSurface,
+        Self::WaterOverlays,
+        Self::LeafAmbientIndexHdr,
+        Self::LeafAmbientIndex,
+        Self::LightingHdr,
+        Self::WorldLightsHdr,
+        Self::LeafAmbientLightingHdr,
+        Self::LeafAmbientLighting,
+        Self::XZipPakFile,
+        Self::FacesHdr,
+        Self::MapFlags,
+        Self::OverlayFades,
+        Self::OverlaySystemLevels,
+        Self::PhysLevel,
+        Self::DispMultiBlend,
+    ];
+}
+
+impl From<LumpId> for usize {
+    fn from(id: LumpId) -> Self {
+        id as usize
+    }
+}
+
+/// Error returned when converting an out-of-range index into a [`LumpId`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidLumpId(pub usize);
+
+impl core::fmt::Display for InvalidLumpId {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "lump index {} is out of range", self.0)
+    }
+}
+
+impl core::error::Error for InvalidLumpId {}
+
+impl TryFrom<usize> for LumpId {
+    type Error = InvalidLumpId;
+
+    fn try_from(index: usize) -> Result<Self, Self::Error> {
+        Self::ALL.get(index).copied().ok_or(InvalidLumpId(index))
+    }
+}
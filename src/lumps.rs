@@ -0,0 +1,1097 @@
+use alloc::{string::String, vec::Vec};
+use core::{cell::Ref, ops::Range};
+
+use zerocopy_derive::*;
+
+use crate::{Bsp, LumpCastError, LumpId, SurfaceFlags};
+
+/// Vertex position ([`LumpId::Vertexes`])
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Debug, Clone, Copy)]
+#[repr(C)]
+pub struct Vertex {
+    /// X coordinate
+    pub x: f32,
+    /// Y coordinate
+    pub y: f32,
+    /// Z coordinate
+    pub z: f32,
+}
+
+/// Plane ([`LumpId::Planes`])
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Debug, Clone, Copy)]
+#[repr(C)]
+pub struct Plane {
+    /// Normal vector
+    pub normal: [f32; 3],
+    /// Distance from origin
+    pub dist: f32,
+    /// Plane axis identifier
+    pub plane_type: i32,
+}
+
+/// Pair of vertex indices ([`LumpId::Edges`])
+pub type Edge = [u16; 2];
+
+/// Texture projection ([`LumpId::TexInfo`])
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Debug, Clone, Copy)]
+#[repr(C)]
+pub struct TexInfo {
+    /// Texture s/t axes, each `[x, y, z, offset]`
+    pub texture_vecs: [[f32; 4]; 2],
+    /// Lightmap s/t axes, each `[x, y, z, offset]`
+    pub lightmap_vecs: [[f32; 4]; 2],
+    /// Surface flags
+    pub flags: i32,
+    /// Index into [`LumpId::TexData`]
+    pub texdata: i32,
+}
+
+/// Texture ([`LumpId::TexData`])
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Debug, Clone, Copy)]
+#[repr(C)]
+pub struct TexData {
+    /// Average color of the texture
+    pub reflectivity: [f32; 3],
+    /// Index into [`LumpId::TexDataStringTable`]
+    pub name_string_table_id: i32,
+    /// Texture width
+    pub width: i32,
+    /// Texture height
+    pub height: i32,
+    /// Texture width as seen in the editor
+    pub view_width: i32,
+    /// Texture height as seen in the editor
+    pub view_height: i32,
+}
+
+/// Face (`dface_t`, [`LumpId::Faces`])
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Debug, Clone, Copy)]
+#[repr(C)]
+pub struct Face {
+    /// Index into [`LumpId::Planes`]
+    pub planenum: u16,
+    /// Whether the face faces opposite to its plane's normal
+    pub side: u8,
+    /// Whether the face is on a node rather than a leaf
+    pub on_node: u8,
+    /// Index of the first surfedge
+    pub firstedge: i32,
+    /// Number of surfedges
+    pub numedges: i16,
+    /// Index into [`LumpId::TexInfo`]
+    pub texinfo: i16,
+    /// Index into [`LumpId::DispInfo`], or -1
+    pub dispinfo: i16,
+    /// Fog volume the face is in
+    pub surface_fog_volume_id: i16,
+    /// Lightmap styles
+    pub styles: [u8; 4],
+    /// Offset into [`LumpId::Lighting`]
+    pub lightofs: i32,
+    /// Face area in units squared
+    pub area: f32,
+    /// Lightmap texture space minimums
+    pub lightmap_mins: [i32; 2],
+    /// Lightmap texture space size
+    pub lightmap_size: [i32; 2],
+    /// Index into [`LumpId::OriginalFaces`]
+    pub orig_face: i32,
+    /// Number of primitives
+    pub num_prims: u16,
+    /// Index of the first primitive
+    pub first_prim_id: u16,
+    /// Lightmap smoothing groups
+    pub smoothing_groups: u32,
+}
+
+const _: () = assert!(size_of::<Face>() == 56);
+
+/// BSP tree node ([`LumpId::Nodes`])
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Debug, Clone, Copy)]
+#[repr(C)]
+pub struct Node {
+    /// Index into [`LumpId::Planes`] of the splitting plane
+    pub planenum: i32,
+    /// Front and back children, negative values are `-(leaf + 1)`
+    pub children: [i32; 2],
+    /// Bounding box minimums
+    pub mins: [i16; 3],
+    /// Bounding box maximums
+    pub maxs: [i16; 3],
+    /// Index of the first face
+    pub firstface: u16,
+    /// Number of faces
+    pub numfaces: u16,
+    /// Area the node is in, or -1 if it spans several
+    pub area: i16,
+    /// Padding
+    pub padding: i16,
+}
+
+const _: () = assert!(size_of::<Node>() == 32);
+
+/// BSP tree leaf ([`LumpId::Leafs`]) of version 20+ files
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Debug, Clone, Copy)]
+#[repr(C)]
+pub struct Leaf {
+    /// Contents flags
+    pub contents: i32,
+    /// Visibility cluster, or -1 if outside the world
+    pub cluster: i16,
+    /// Area in the low 9 bits, flags in the high 7
+    pub area_flags: i16,
+    /// Bounding box minimums
+    pub mins: [i16; 3],
+    /// Bounding box maximums
+    pub maxs: [i16; 3],
+    /// Index into [`LumpId::LeafFaces`] of the first face
+    pub firstleafface: u16,
+    /// Number of faces
+    pub numleaffaces: u16,
+    /// Index into [`LumpId::LeafBrushes`] of the first brush
+    pub firstleafbrush: u16,
+    /// Number of brushes
+    pub numleafbrushes: u16,
+    /// Index into [`LumpId::LeafWaterData`], or -1
+    pub leaf_water_data_id: i16,
+    /// Padding
+    pub padding: i16,
+}
+
+const _: () = assert!(size_of::<Leaf>() == 32);
+
+/// BSP tree leaf ([`LumpId::Leafs`]) of version 19 and older files
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Debug, Clone, Copy)]
+#[repr(C)]
+pub struct LeafV0 {
+    /// Contents flags
+    pub contents: i32,
+    /// Visibility cluster, or -1 if outside the world
+    pub cluster: i16,
+    /// Area in the low 9 bits, flags in the high 7
+    pub area_flags: i16,
+    /// Bounding box minimums
+    pub mins: [i16; 3],
+    /// Bounding box maximums
+    pub maxs: [i16; 3],
+    /// Index into [`LumpId::LeafFaces`] of the first face
+    pub firstleafface: u16,
+    /// Number of faces
+    pub numleaffaces: u16,
+    /// Index into [`LumpId::LeafBrushes`] of the first brush
+    pub firstleafbrush: u16,
+    /// Number of brushes
+    pub numleafbrushes: u16,
+    /// Index into [`LumpId::LeafWaterData`], or -1
+    pub leaf_water_data_id: i16,
+    /// Ambient light cube, one `ColorRGBExp32` per axis direction
+    pub ambient_lighting: [[u8; 4]; 6],
+    /// Padding
+    pub padding: i16,
+}
+
+const _: () = assert!(size_of::<LeafV0>() == 56);
+
+impl From<LeafV0> for Leaf {
+    fn from(leaf: LeafV0) -> Self {
+        Self {
+            contents: leaf.contents,
+            cluster: leaf.cluster,
+            area_flags: leaf.area_flags,
+            mins: leaf.mins,
+            maxs: leaf.maxs,
+            firstleafface: leaf.firstleafface,
+            numleaffaces: leaf.numleaffaces,
+            firstleafbrush: leaf.firstleafbrush,
+            numleafbrushes: leaf.numleafbrushes,
+            leaf_water_data_id: leaf.leaf_water_data_id,
+            padding: leaf.padding,
+        }
+    }
+}
+
+/// Leaves in the layout matching the file version, see [`Bsp::leaves`]
+#[derive(Debug)]
+pub enum Leaves<'b> {
+    /// Version 19 and older, with inline ambient lighting
+    V0(Ref<'b, [LeafV0]>),
+    /// Version 20+
+    V1(Ref<'b, [Leaf]>),
+}
+
+impl Leaves<'_> {
+    /// Number of leaves
+    pub fn len(&self) -> usize {
+        match self {
+            Self::V0(leaves) => leaves.len(),
+            Self::V1(leaves) => leaves.len(),
+        }
+    }
+
+    /// Whether there are no leaves
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Leaf at `index` with the version-independent fields, or `None` if out of range
+    pub fn get(&self, index: usize) -> Option<Leaf> {
+        match self {
+            Self::V0(leaves) => leaves.get(index).copied().map(Leaf::from),
+            Self::V1(leaves) => leaves.get(index).copied(),
+        }
+    }
+}
+
+/// Error returned by [`Bsp::leaves`]
+#[derive(Debug)]
+pub enum LeafError {
+    /// [`LumpId::Leafs`] couldn't be cast to a slice of [`LeafV0`]
+    V0(LumpCastError<[LeafV0]>),
+    /// [`LumpId::Leafs`] couldn't be cast to a slice of [`Leaf`]
+    V1(LumpCastError<[Leaf]>),
+}
+
+impl core::fmt::Display for LeafError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::V0(_) => f.write_str("leaf lump is malformed (version 0 layout)"),
+            Self::V1(_) => f.write_str("leaf lump is malformed (version 1 layout)"),
+        }
+    }
+}
+
+impl core::error::Error for LeafError {}
+
+/// Brush model (`dmodel_t`, [`LumpId::Models`])
+///
+/// Model 0 is the world, the rest belong to brush entities.
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Debug, Clone, Copy)]
+#[repr(C)]
+pub struct Model {
+    /// Bounding box minimums
+    pub mins: [f32; 3],
+    /// Bounding box maximums
+    pub maxs: [f32; 3],
+    /// Origin
+    pub origin: [f32; 3],
+    /// Index into [`LumpId::Nodes`] of the model's root node
+    pub headnode: i32,
+    /// Index of the first face
+    pub firstface: i32,
+    /// Number of faces
+    pub numfaces: i32,
+}
+
+const _: () = assert!(size_of::<Model>() == 48);
+
+/// Light used by the compiler and engine ([`LumpId::WorldLights`] and
+/// [`LumpId::WorldLightsHdr`])
+///
+/// This is the 88-byte layout written by the v19/20 compilers; other layouts fail to cast.
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Debug, Clone, Copy)]
+#[repr(C)]
+pub struct WorldLight {
+    /// Position
+    pub origin: [f32; 3],
+    /// Color and brightness
+    pub intensity: [f32; 3],
+    /// Direction for spot and surface lights
+    pub normal: [f32; 3],
+    /// Visibility cluster
+    pub cluster: i32,
+    /// Light type (`emittype_t`)
+    pub light_type: i32,
+    /// Light style
+    pub style: i32,
+    /// Cosine of the inner spotlight cone angle
+    pub stopdot: f32,
+    /// Cosine of the outer spotlight cone angle
+    pub stopdot2: f32,
+    /// Spotlight falloff exponent
+    pub exponent: f32,
+    /// Distance beyond which the light has no effect
+    pub radius: f32,
+    /// Constant attenuation factor
+    pub constant_attn: f32,
+    /// Linear attenuation factor
+    pub linear_attn: f32,
+    /// Quadratic attenuation factor
+    pub quadratic_attn: f32,
+    /// Light flags
+    pub flags: i32,
+    /// Index into [`LumpId::TexInfo`] for surface lights
+    pub texinfo: i32,
+    /// Entity that this light belongs to
+    pub owner: i32,
+}
+
+const _: () = assert!(size_of::<WorldLight>() == 88);
+
+/// Neighbor along part of a displacement edge
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Debug, Clone, Copy)]
+#[repr(C)]
+pub struct DispSubNeighbor {
+    /// Index into [`LumpId::DispInfo`], or 0xFFFF if there's no neighbor
+    pub neighbor: u16,
+    /// Orientation of the neighbor relative to this displacement
+    pub neighbor_orientation: u8,
+    /// Part of this edge the neighbor spans
+    pub span: u8,
+    /// Part of the neighbor's edge this displacement spans
+    pub neighbor_span: u8,
+    /// Padding
+    pub padding: u8,
+}
+
+/// Neighbors along a displacement edge
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Debug, Clone, Copy)]
+#[repr(C)]
+pub struct DispNeighbor {
+    /// Neighbors of each half of the edge
+    pub sub_neighbors: [DispSubNeighbor; 2],
+}
+
+/// Neighbors touching a displacement corner
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Debug, Clone, Copy)]
+#[repr(C)]
+pub struct DispCornerNeighbors {
+    /// Indices into [`LumpId::DispInfo`]
+    pub neighbors: [u16; 4],
+    /// Number of valid entries in `neighbors`
+    pub num_neighbors: u8,
+    /// Padding
+    pub padding: u8,
+}
+
+/// Displacement surface (`ddispinfo_t`, [`LumpId::DispInfo`])
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Debug, Clone, Copy)]
+#[repr(C)]
+pub struct DispInfo {
+    /// Position of the first corner
+    pub start_position: [f32; 3],
+    /// Index into [`LumpId::DispVerts`] of the first vertex
+    pub disp_vert_start: i32,
+    /// Index into [`LumpId::DispTris`] of the first triangle
+    pub disp_tri_start: i32,
+    /// Subdivision power, 2 to 4
+    pub power: i32,
+    /// Minimum tessellation
+    pub min_tess: i32,
+    /// Lighting smoothing angle
+    pub smoothing_angle: f32,
+    /// Contents flags
+    pub contents: i32,
+    /// Index into [`LumpId::Faces`] of the face the displacement replaces
+    pub map_face: u16,
+    /// Padding
+    pub padding: u16,
+    /// Index into [`LumpId::DispLightmapAlphas`]
+    pub lightmap_alpha_start: i32,
+    /// Index into [`LumpId::DispLightmapSamplePositions`]
+    pub lightmap_sample_position_start: i32,
+    /// Neighbors along each edge
+    pub edge_neighbors: [DispNeighbor; 4],
+    /// Neighbors touching each corner
+    pub corner_neighbors: [DispCornerNeighbors; 4],
+    /// Bit vector of vertices that are allowed to be tessellated
+    pub allowed_verts: [u32; 10],
+}
+
+const _: () = assert!(size_of::<DispSubNeighbor>() == 6);
+const _: () = assert!(size_of::<DispCornerNeighbors>() == 10);
+const _: () = assert!(size_of::<DispInfo>() == 176);
+
+impl DispInfo {
+    /// Vertices along each side of the grid, `(1 << power) + 1`, or `None` if `power` is
+    /// out of range
+    pub fn grid_size(&self) -> Option<usize> {
+        let power = u32::try_from(self.power).ok()?;
+        Some(1usize.checked_shl(power)? + 1)
+    }
+
+    /// Number of vertices in the grid, the square of [`DispInfo::grid_size`]
+    pub fn vertex_count(&self) -> Option<usize> {
+        let size = self.grid_size()?;
+        size.checked_mul(size)
+    }
+}
+
+/// Displacement vertex (`dDispVert`, [`LumpId::DispVerts`])
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Debug, Clone, Copy)]
+#[repr(C)]
+pub struct DispVert {
+    /// Direction of the offset from the base surface
+    pub vec: [f32; 3],
+    /// Offset distance along `vec`
+    pub dist: f32,
+    /// Blend alpha between the two textures
+    pub alpha: f32,
+}
+
+/// `env_cubemap` sample (`dcubemapsample_t`, [`LumpId::Cubemaps`])
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Debug, Clone, Copy)]
+#[repr(C)]
+pub struct CubemapSample {
+    /// World position
+    pub origin: [i32; 3],
+    /// Resolution as a power of two offset by 1, or 0 for the default
+    pub size: u8,
+    /// Padding
+    pub padding: [u8; 3],
+}
+
+// 13 bytes of fields, padded to the alignment of `origin` as the engine writes them
+const _: () = assert!(size_of::<CubemapSample>() == 16);
+
+/// Decal overlay (`doverlay_t`, [`LumpId::Overlays`])
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Debug, Clone, Copy)]
+#[repr(C)]
+pub struct Overlay {
+    /// Identifier, unique within the map
+    pub id: i32,
+    /// Index into [`LumpId::TexInfo`]
+    pub tex_info: i16,
+    /// Face count in the low 14 bits and render order in the high 2, see
+    /// [`Overlay::face_count`] and [`Overlay::render_order`]
+    pub face_count_and_render_order: u16,
+    /// Indices into [`LumpId::Faces`], of which the first [`Overlay::face_count`] are used
+    pub faces: [i32; OVERLAY_MAX_FACES],
+    /// Texture coordinate range along U
+    pub u: [f32; 2],
+    /// Texture coordinate range along V
+    pub v: [f32; 2],
+    /// Corners of the overlay, relative to `origin` in the overlay's basis
+    pub uv_points: [[f32; 3]; 4],
+    /// World position
+    pub origin: [f32; 3],
+    /// Normal of the surface the overlay is projected onto
+    pub basis_normal: [f32; 3],
+}
+
+const _: () = assert!(size_of::<Overlay>() == 352);
+
+/// Maximum number of faces an [`Overlay`] can cover
+pub const OVERLAY_MAX_FACES: usize = 64;
+
+impl Overlay {
+    const FACE_COUNT_MASK: u16 = 0x3fff;
+    const RENDER_ORDER_SHIFT: u32 = 14;
+
+    /// Number of entries of `faces` in use
+    pub fn face_count(&self) -> u16 {
+        self.face_count_and_render_order & Self::FACE_COUNT_MASK
+    }
+
+    /// Render order among overlays on the same faces, from 0 to 3
+    pub fn render_order(&self) -> u8 {
+        (self.face_count_and_render_order >> Self::RENDER_ORDER_SHIFT) as u8
+    }
+
+    /// Set the number of entries of `faces` in use, keeping the render order
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` doesn't fit in 14 bits.
+    pub fn set_face_count(&mut self, count: u16) {
+        assert!(count <= Self::FACE_COUNT_MASK, "face count out of range");
+        self.face_count_and_render_order =
+            (self.face_count_and_render_order & !Self::FACE_COUNT_MASK) | count;
+    }
+
+    /// Set the render order, keeping the face count
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order` is greater than 3.
+    pub fn set_render_order(&mut self, order: u8) {
+        assert!(order <= 3, "render order out of range");
+        self.face_count_and_render_order =
+            self.face_count() | (u16::from(order) << Self::RENDER_ORDER_SHIFT);
+    }
+
+    /// Used entries of `faces`, clamped to its length
+    pub fn used_faces(&self) -> &[i32] {
+        &self.faces[..usize::from(self.face_count()).min(OVERLAY_MAX_FACES)]
+    }
+}
+
+/// Area (`darea_t`, [`LumpId::Areas`])
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Debug, Clone, Copy)]
+#[repr(C)]
+pub struct Area {
+    /// Number of area portals
+    pub numareaportals: i32,
+    /// Index into [`LumpId::AreaPortals`] of the first area portal
+    pub firstareaportal: i32,
+}
+
+/// Portal between two areas (`dareaportal_t`, [`LumpId::AreaPortals`])
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Debug, Clone, Copy)]
+#[repr(C)]
+pub struct AreaPortal {
+    /// Key shared by both sides of the portal, matched against `func_areaportal` entities
+    pub portal_key: u16,
+    /// Index into [`LumpId::Areas`] of the area on the other side
+    pub other_area: u16,
+    /// Index into [`LumpId::ClipPortalVerts`] of the first vertex of the portal's outline
+    pub first_clip_portal_vert: u16,
+    /// Number of vertices in the portal's outline
+    pub clip_portal_verts: u16,
+    /// Index into [`LumpId::Planes`]
+    pub plane_num: i32,
+}
+
+const _: () = assert!(size_of::<AreaPortal>() == 12);
+
+/// Pre-tessellated surface (`dprimitive_t`, [`LumpId::Primitives`]), used in place of a
+/// face's own polygon to avoid T-junctions, see [`Bsp::face_primitives`]
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Debug, Clone, Copy)]
+#[repr(C)]
+pub struct Primitive {
+    /// [`Primitive::TRI_LIST`] or [`Primitive::TRI_STRIP`]
+    pub prim_type: u8,
+    /// Alignment padding
+    pub padding: u8,
+    /// Index into [`LumpId::PrimIndices`] of the first index
+    pub first_index: u16,
+    /// Number of indices
+    pub index_count: u16,
+    /// Index into [`LumpId::PrimVerts`] of the first vertex
+    pub first_vert: u16,
+    /// Number of vertices
+    pub vert_count: u16,
+}
+
+const _: () = assert!(size_of::<Primitive>() == 10);
+
+impl Primitive {
+    /// Indices form separate triangles
+    pub const TRI_LIST: u8 = 0;
+    /// Indices form a triangle strip
+    pub const TRI_STRIP: u8 = 1;
+
+    /// Range of indices into [`Bsp::prim_indices`]
+    pub fn indices(&self) -> Range<usize> {
+        let start = usize::from(self.first_index);
+        start..start + usize::from(self.index_count)
+    }
+
+    /// Range of indices into [`Bsp::prim_verts`]
+    pub fn verts(&self) -> Range<usize> {
+        let start = usize::from(self.first_vert);
+        start..start + usize::from(self.vert_count)
+    }
+}
+
+/// Primitive vertex position (`dprimvert_t`, [`LumpId::PrimVerts`])
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Debug, Clone, Copy)]
+#[repr(C)]
+pub struct PrimVert {
+    /// Position
+    pub pos: [f32; 3],
+}
+
+const _: () = assert!(size_of::<PrimVert>() == 12);
+
+/// Brush ([`LumpId::Brushes`])
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Debug, Clone, Copy)]
+#[repr(C)]
+pub struct Brush {
+    /// Index of the first brush side
+    pub firstside: i32,
+    /// Number of brush sides
+    pub numsides: i32,
+    /// Contents flags
+    pub contents: i32,
+}
+
+/// Brush side ([`LumpId::BrushSides`])
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Debug, Clone, Copy)]
+#[repr(C)]
+pub struct BrushSide {
+    /// Index into [`LumpId::Planes`]
+    pub planenum: u16,
+    /// Index into [`LumpId::TexInfo`]
+    pub texinfo: i16,
+    /// Index into [`LumpId::DispInfo`], or -1
+    pub dispinfo: i16,
+    /// Whether the side is a bevel plane
+    pub bevel: i16,
+}
+
+/// Error returned when resolving the sides of a [`Brush`]
+#[derive(Debug)]
+pub enum BrushSideError {
+    /// [`LumpId::BrushSides`] couldn't be cast to a slice of [`BrushSide`]
+    Lump(LumpCastError<[BrushSide]>),
+    /// Brush refers to sides outside of [`LumpId::BrushSides`]
+    OutOfBounds {
+        /// First side of the brush
+        firstside: i32,
+        /// Number of sides of the brush
+        numsides: i32,
+        /// Number of brush sides in the lump
+        len: usize,
+    },
+}
+
+impl core::fmt::Display for BrushSideError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Lump(_) => f.write_str("brush side lump is malformed"),
+            Self::OutOfBounds {
+                firstside,
+                numsides,
+                len,
+            } => write!(
+                f,
+                "brush sides {firstside}..{firstside}+{numsides} exceed brush side count {len}"
+            ),
+        }
+    }
+}
+
+impl core::error::Error for BrushSideError {}
+
+/// Error returned by [`Bsp::faces_in_leaf`] and [`Bsp::brushes_in_leaf`]
+#[derive(Debug)]
+pub enum LeafListError {
+    /// [`LumpId::LeafFaces`] or [`LumpId::LeafBrushes`] couldn't be cast to a slice of
+    /// indices
+    Lump(LumpCastError<[u16]>),
+    /// Leaf refers to entries outside of the lump
+    OutOfBounds {
+        /// First entry of the leaf
+        first: u16,
+        /// Number of entries of the leaf
+        count: u16,
+        /// Number of entries in the lump
+        len: usize,
+    },
+}
+
+impl core::fmt::Display for LeafListError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Lump(_) => f.write_str("leaf index lump is malformed"),
+            Self::OutOfBounds { first, count, len } => write!(
+                f,
+                "leaf entries {first}..{first}+{count} exceed lump entry count {len}"
+            ),
+        }
+    }
+}
+
+impl core::error::Error for LeafListError {}
+
+/// Error returned by [`Bsp::texdata_strings`]
+#[derive(Debug)]
+pub enum TexDataStringError {
+    /// [`LumpId::TexDataStringTable`] couldn't be cast to a slice of offsets
+    Table(LumpCastError<[i32]>),
+    /// Table entry points outside of [`LumpId::TexDataStringData`]
+    OutOfBounds {
+        /// Index into the table
+        index: usize,
+        /// Offset stored in the table
+        offset: i32,
+    },
+    /// String runs to the end of [`LumpId::TexDataStringData`] without a NUL terminator
+    Unterminated {
+        /// Index into the table
+        index: usize,
+    },
+}
+
+impl core::fmt::Display for TexDataStringError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Table(_) => f.write_str("texture string table is malformed"),
+            Self::OutOfBounds { index, offset } => write!(
+                f,
+                "texture string {index} at offset {offset} is outside of the string data"
+            ),
+            Self::Unterminated { index } => {
+                write!(f, "texture string {index} is missing its NUL terminator")
+            }
+        }
+    }
+}
+
+impl core::error::Error for TexDataStringError {}
+
+impl Bsp<'_> {
+    /// Cast [`LumpId::Vertexes`] to a slice of [`Vertex`]
+    pub fn vertices(&self) -> Result<Ref<'_, [Vertex]>, LumpCastError<[Vertex]>> {
+        self.lump_cast(LumpId::Vertexes)
+    }
+
+    /// Cast [`LumpId::Planes`] to a slice of [`Plane`]
+    pub fn planes(&self) -> Result<Ref<'_, [Plane]>, LumpCastError<[Plane]>> {
+        self.lump_cast(LumpId::Planes)
+    }
+
+    /// Cast [`LumpId::Edges`] to a slice of [`Edge`]
+    pub fn edges(&self) -> Result<Ref<'_, [Edge]>, LumpCastError<[Edge]>> {
+        self.lump_cast(LumpId::Edges)
+    }
+
+    /// Cast [`LumpId::TexInfo`] to a slice of [`TexInfo`]
+    pub fn texinfo(&self) -> Result<Ref<'_, [TexInfo]>, LumpCastError<[TexInfo]>> {
+        self.lump_cast(LumpId::TexInfo)
+    }
+
+    /// Cast [`LumpId::TexData`] to a slice of [`TexData`]
+    pub fn texdata(&self) -> Result<Ref<'_, [TexData]>, LumpCastError<[TexData]>> {
+        self.lump_cast(LumpId::TexData)
+    }
+
+    /// Material path of a texinfo, resolved through [`LumpId::TexData`],
+    /// [`LumpId::TexDataStringTable`] and [`LumpId::TexDataStringData`]
+    ///
+    /// Returns `None` if any lump along the way is malformed, an index or offset is out
+    /// of range, or the name isn't valid UTF-8.
+    pub fn material_name(&self, texinfo_index: usize) -> Option<Ref<'_, str>> {
+        let texdata = self.texinfo().ok()?.get(texinfo_index)?.texdata;
+        let string_id = self
+            .texdata()
+            .ok()?
+            .get(usize::try_from(texdata).ok()?)?
+            .name_string_table_id;
+        let offset = *self
+            .texdata_string_table()
+            .ok()?
+            .get(usize::try_from(string_id).ok()?)?;
+        let offset = usize::try_from(offset).ok()?;
+
+        let (_, data) = self.lump(LumpId::TexDataStringData);
+        Ref::filter_map(data, |data| {
+            let name = data.get(offset..)?;
+            core::str::from_utf8(nul_terminated(name)?).ok()
+        })
+        .ok()
+    }
+
+    /// Cast [`LumpId::TexDataStringTable`] to a slice of offsets into
+    /// [`LumpId::TexDataStringData`]
+    pub fn texdata_string_table(&self) -> Result<Ref<'_, [i32]>, LumpCastError<[i32]>> {
+        self.lump_cast(LumpId::TexDataStringTable)
+    }
+
+    /// Every string of [`LumpId::TexDataStringData`], in the order of
+    /// [`Bsp::texdata_string_table`]
+    ///
+    /// Invalid UTF-8 is replaced with U+FFFD, see [`Bsp::material_name`] for borrowing a
+    /// single valid string instead.
+    pub fn texdata_strings(&self) -> Result<Vec<String>, TexDataStringError> {
+        let table = self
+            .texdata_string_table()
+            .map_err(TexDataStringError::Table)?;
+        let (_, data) = self.lump(LumpId::TexDataStringData);
+
+        table
+            .iter()
+            .enumerate()
+            .map(|(index, &offset)| {
+                let string = usize::try_from(offset)
+                    .ok()
+                    .and_then(|offset| data.get(offset..))
+                    .ok_or(TexDataStringError::OutOfBounds { index, offset })?;
+                let string =
+                    nul_terminated(string).ok_or(TexDataStringError::Unterminated { index })?;
+                Ok(String::from_utf8_lossy(string).into_owned())
+            })
+            .collect()
+    }
+
+    /// Cast [`LumpId::Nodes`] to a slice of [`Node`]
+    pub fn nodes(&self) -> Result<Ref<'_, [Node]>, LumpCastError<[Node]>> {
+        self.lump_cast(LumpId::Nodes)
+    }
+
+    /// Cast [`LumpId::Leafs`] to the leaf layout used by the file
+    ///
+    /// Files of version 19 and older store [`LeafV0`], newer ones store [`Leaf`].
+    pub fn leaves(&self) -> Result<Leaves<'_>, LeafError> {
+        if self.header.version <= 19 {
+            self.lump_cast(LumpId::Leafs)
+                .map(Leaves::V0)
+                .map_err(LeafError::V0)
+        } else {
+            self.lump_cast(LumpId::Leafs)
+                .map(Leaves::V1)
+                .map_err(LeafError::V1)
+        }
+    }
+
+    /// Cast [`LumpId::LeafFaces`] to a slice of face indices
+    pub fn leaf_faces(&self) -> Result<Ref<'_, [u16]>, LumpCastError<[u16]>> {
+        self.lump_cast(LumpId::LeafFaces)
+    }
+
+    /// Cast [`LumpId::LeafBrushes`] to a slice of brush indices
+    pub fn leaf_brushes(&self) -> Result<Ref<'_, [u16]>, LumpCastError<[u16]>> {
+        self.lump_cast(LumpId::LeafBrushes)
+    }
+
+    /// Indices into [`LumpId::Faces`] of the faces in `leaf`
+    pub fn faces_in_leaf(
+        &self,
+        leaf: &Leaf,
+    ) -> Result<impl Iterator<Item = u16> + '_, LeafListError> {
+        let faces = self.leaf_faces().map_err(LeafListError::Lump)?;
+        leaf_list(faces, leaf.firstleafface, leaf.numleaffaces)
+    }
+
+    /// Indices into [`LumpId::Brushes`] of the brushes in `leaf`
+    pub fn brushes_in_leaf(
+        &self,
+        leaf: &Leaf,
+    ) -> Result<impl Iterator<Item = u16> + '_, LeafListError> {
+        let brushes = self.leaf_brushes().map_err(LeafListError::Lump)?;
+        leaf_list(brushes, leaf.firstleafbrush, leaf.numleafbrushes)
+    }
+
+    /// Cast [`LumpId::Faces`] to a slice of [`Face`]
+    pub fn faces(&self) -> Result<Ref<'_, [Face]>, LumpCastError<[Face]>> {
+        self.lump_cast(LumpId::Faces)
+    }
+
+    /// Faces whose texture projection isn't [`SurfaceFlags::NODRAW`], along with their
+    /// index into [`LumpId::Faces`]
+    ///
+    /// Faces with an out of range texinfo index are kept. Returns `None` if either lump
+    /// is malformed.
+    pub fn visible_faces(&self) -> Option<impl Iterator<Item = (usize, Face)> + '_> {
+        let faces = self.faces().ok()?;
+        let texinfo = self.texinfo().ok()?;
+        Some((0..faces.len()).filter_map(move |index| {
+            let face = faces[index];
+            let nodraw = usize::try_from(face.texinfo)
+                .ok()
+                .and_then(|i| texinfo.get(i))
+                .is_some_and(|t| t.surface_flags().contains(SurfaceFlags::NODRAW));
+            (!nodraw).then_some((index, face))
+        }))
+    }
+
+    /// Cast [`LumpId::Primitives`] to a slice of [`Primitive`]
+    pub fn primitives(&self) -> Result<Ref<'_, [Primitive]>, LumpCastError<[Primitive]>> {
+        self.lump_cast(LumpId::Primitives)
+    }
+
+    /// Cast [`LumpId::PrimVerts`] to a slice of [`PrimVert`]
+    pub fn prim_verts(&self) -> Result<Ref<'_, [PrimVert]>, LumpCastError<[PrimVert]>> {
+        self.lump_cast(LumpId::PrimVerts)
+    }
+
+    /// Cast [`LumpId::PrimIndices`] to a slice of the vertex indices of every primitive
+    pub fn prim_indices(&self) -> Result<Ref<'_, [u16]>, LumpCastError<[u16]>> {
+        self.lump_cast(LumpId::PrimIndices)
+    }
+
+    /// Range of indices into [`Bsp::primitives`] belonging to `face`
+    ///
+    /// Empty for faces rendered from their own polygon. The range isn't checked against
+    /// the primitive lump.
+    pub fn face_primitives(&self, face: &Face) -> Range<usize> {
+        let start = usize::from(face.first_prim_id);
+        start..start + usize::from(face.num_prims)
+    }
+
+    /// Cast [`LumpId::Models`] to a slice of [`Model`]
+    pub fn models(&self) -> Result<Ref<'_, [Model]>, LumpCastError<[Model]>> {
+        self.lump_cast(LumpId::Models)
+    }
+
+    /// Range of indices into [`Bsp::faces`] belonging to `model`
+    ///
+    /// Negative values are treated as zero. The range isn't checked against the face lump.
+    pub fn model_faces(&self, model: &Model) -> Range<usize> {
+        let start = usize::try_from(model.firstface).unwrap_or(0);
+        let len = usize::try_from(model.numfaces).unwrap_or(0);
+        start..start.saturating_add(len)
+    }
+
+    /// Cast [`LumpId::WorldLights`] to a slice of [`WorldLight`]
+    pub fn world_lights(&self) -> Result<Ref<'_, [WorldLight]>, LumpCastError<[WorldLight]>> {
+        self.lump_cast(LumpId::WorldLights)
+    }
+
+    /// Cast [`LumpId::WorldLightsHdr`] to a slice of [`WorldLight`]
+    pub fn world_lights_hdr(&self) -> Result<Ref<'_, [WorldLight]>, LumpCastError<[WorldLight]>> {
+        self.lump_cast(LumpId::WorldLightsHdr)
+    }
+
+    /// Cast [`LumpId::DispInfo`] to a slice of [`DispInfo`]
+    pub fn disp_infos(&self) -> Result<Ref<'_, [DispInfo]>, LumpCastError<[DispInfo]>> {
+        self.lump_cast(LumpId::DispInfo)
+    }
+
+    /// Cast [`LumpId::DispVerts`] to a slice of [`DispVert`]
+    pub fn disp_verts(&self) -> Result<Ref<'_, [DispVert]>, LumpCastError<[DispVert]>> {
+        self.lump_cast(LumpId::DispVerts)
+    }
+
+    /// Cast [`LumpId::Cubemaps`] to a slice of [`CubemapSample`]
+    pub fn cubemaps(&self) -> Result<Ref<'_, [CubemapSample]>, LumpCastError<[CubemapSample]>> {
+        self.lump_cast(LumpId::Cubemaps)
+    }
+
+    /// Cast [`LumpId::Overlays`] to a slice of [`Overlay`]
+    pub fn overlays(&self) -> Result<Ref<'_, [Overlay]>, LumpCastError<[Overlay]>> {
+        self.lump_cast(LumpId::Overlays)
+    }
+
+    /// Cast [`LumpId::Areas`] to a slice of [`Area`]
+    pub fn areas(&self) -> Result<Ref<'_, [Area]>, LumpCastError<[Area]>> {
+        self.lump_cast(LumpId::Areas)
+    }
+
+    /// Cast [`LumpId::AreaPortals`] to a slice of [`AreaPortal`]
+    pub fn area_portals(&self) -> Result<Ref<'_, [AreaPortal]>, LumpCastError<[AreaPortal]>> {
+        self.lump_cast(LumpId::AreaPortals)
+    }
+
+    /// Cast [`LumpId::Brushes`] to a slice of [`Brush`]
+    pub fn brushes(&self) -> Result<Ref<'_, [Brush]>, LumpCastError<[Brush]>> {
+        self.lump_cast(LumpId::Brushes)
+    }
+
+    /// Cast [`LumpId::BrushSides`] to a slice of [`BrushSide`]
+    pub fn brush_sides(&self) -> Result<Ref<'_, [BrushSide]>, LumpCastError<[BrushSide]>> {
+        self.lump_cast(LumpId::BrushSides)
+    }
+
+    /// Plane indices of each side of `brush`
+    pub fn brush_side_planes(
+        &self,
+        brush: &Brush,
+    ) -> Result<impl Iterator<Item = u16> + '_, BrushSideError> {
+        let sides = self.brush_sides().map_err(BrushSideError::Lump)?;
+        let range = usize::try_from(brush.firstside)
+            .ok()
+            .zip(usize::try_from(brush.numsides).ok())
+            .and_then(|(start, len)| Some(start..start.checked_add(len)?))
+            .filter(|range| range.end <= sides.len())
+            .ok_or(BrushSideError::OutOfBounds {
+                firstside: brush.firstside,
+                numsides: brush.numsides,
+                len: sides.len(),
+            })?;
+
+        Ok(range.map(move |i| sides[i].planenum))
+    }
+
+    /// Cast [`LumpId::SurfEdges`] to a slice of signed edge indices
+    pub fn surfedges(&self) -> Result<Ref<'_, [i32]>, LumpCastError<[i32]>> {
+        self.lump_cast(LumpId::SurfEdges)
+    }
+
+    /// Resolve a surfedge to the (start, end) vertex indices of its edge
+    ///
+    /// A negative surfedge refers to the same edge traversed in reverse, so the vertex
+    /// order is swapped. Returns `None` if the edge lump is malformed or the index is
+    /// out of range.
+    pub fn edge_vertices(&self, surfedge: i32) -> Option<(u16, u16)> {
+        let edges = self.edges().ok()?;
+        let [a, b] = *edges.get(surfedge.unsigned_abs() as usize)?;
+
+        if surfedge < 0 {
+            Some((b, a))
+        } else {
+            Some((a, b))
+        }
+    }
+}
+
+/// Bytes of `data` up to the first NUL, or `None` if there's none
+///
+/// Texture names are NUL-terminated strings packed into a single buffer.
+fn nul_terminated(data: &[u8]) -> Option<&[u8]> {
+    let len = data.iter().position(|&b| b == 0)?;
+    Some(&data[..len])
+}
+
+/// Entries `first..first + count` of a leaf index lump
+fn leaf_list(
+    list: Ref<'_, [u16]>,
+    first: u16,
+    count: u16,
+) -> Result<impl Iterator<Item = u16> + '_, LeafListError> {
+    let range = usize::from(first)..usize::from(first) + usize::from(count);
+    if range.end > list.len() {
+        return Err(LeafListError::OutOfBounds {
+            first,
+            count,
+            len: list.len(),
+        });
+    }
+    Ok(range.map(move |i| list[i]))
+}
+
+#[cfg(test)]
+mod tests {
+    use zerocopy::IntoBytes;
+
+    use super::*;
+    use crate::{BspBuilder, LumpMetadata};
+
+    fn metadata() -> LumpMetadata {
+        LumpMetadata {
+            version: 0,
+            identifier: [0; 4],
+        }
+    }
+
+    #[test]
+    fn edge_vertices_follow_surfedge_sign() {
+        let edges: [[u16; 2]; 3] = [[0, 0], [3, 7], [5, 9]];
+        let bsp = BspBuilder::new(*b"VBSP", 20)
+            .set_lump(LumpId::Edges, metadata(), edges.as_bytes().to_vec())
+            .build();
+
+        assert_eq!(bsp.edge_vertices(1), Some((3, 7)));
+        assert_eq!(bsp.edge_vertices(2), Some((5, 9)));
+        assert_eq!(bsp.edge_vertices(-1), Some((7, 3)));
+        assert_eq!(bsp.edge_vertices(-2), Some((9, 5)));
+        assert_eq!(bsp.edge_vertices(3), None);
+        assert_eq!(bsp.edge_vertices(i32::MIN), None);
+    }
+
+    #[test]
+    fn model_layout() {
+        use core::mem::offset_of;
+
+        assert_eq!(offset_of!(Model, mins), 0);
+        assert_eq!(offset_of!(Model, maxs), 12);
+        assert_eq!(offset_of!(Model, origin), 24);
+        assert_eq!(offset_of!(Model, headnode), 36);
+        assert_eq!(offset_of!(Model, firstface), 40);
+        assert_eq!(offset_of!(Model, numfaces), 44);
+
+        let mut record = [0u8; 48];
+        for (i, chunk) in record.chunks_exact_mut(4).enumerate() {
+            let value = if i < 9 {
+                (i as f32).to_bits()
+            } else {
+                i as u32
+            };
+            chunk.copy_from_slice(&value.to_le_bytes());
+        }
+        let bsp = BspBuilder::new(*b"VBSP", 20)
+            .set_lump(LumpId::Models, metadata(), record.to_vec())
+            .build();
+
+        let models = bsp.models().unwrap();
+        assert_eq!(models.len(), 1);
+        let model = &models[0];
+        assert_eq!(model.mins, [0.0, 1.0, 2.0]);
+        assert_eq!(model.maxs, [3.0, 4.0, 5.0]);
+        assert_eq!(model.origin, [6.0, 7.0, 8.0]);
+        assert_eq!(model.headnode, 9);
+        assert_eq!(model.firstface, 10);
+        assert_eq!(model.numfaces, 11);
+        assert_eq!(bsp.model_faces(model), 10..21);
+    }
+}
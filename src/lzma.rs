@@ -0,0 +1,245 @@
+use alloc::{borrow::Cow, vec::Vec};
+use core::{borrow::Borrow, cell::Ref};
+use std::io::{Read, Write};
+
+use lzma_rust2::{LzmaOptions, LzmaReader, LzmaWriter};
+use zerocopy::{FromBytes, IntoBytes};
+use zerocopy_derive::*;
+
+use crate::{Bsp, LumpData, LumpId, LUMP_ALIGNMENT};
+
+/// Identifier at the start of an LZMA-compressed lump
+pub const LZMA_ID: [u8; 4] = *b"LZMA";
+
+/// Header preceding the LZMA stream of a compressed lump
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Debug, Clone, Copy)]
+#[repr(C, packed)]
+struct LzmaHeader {
+    /// Always [`LZMA_ID`]
+    id: [u8; 4],
+    /// Length of data after decompression
+    actual_size: u32,
+    /// Length of compressed stream following the header
+    lzma_size: u32,
+    /// LZMA properties byte followed by the dictionary size
+    properties: [u8; 5],
+}
+
+/// Most bytes preallocated per compressed byte when decompressing
+///
+/// The header's uncompressed size is untrusted, so a tiny lump could otherwise request a
+/// huge allocation up front. Output past this still grows as it's decoded.
+const MAX_PREALLOC_RATIO: usize = 8;
+
+/// Error returned when decompressing a lump
+#[derive(Debug)]
+pub enum DecompressError {
+    /// Compressed stream is shorter than its header describes
+    Truncated,
+    /// Decompressed data doesn't have the length stored in the header
+    SizeMismatch {
+        /// Length stored in the header
+        expected: usize,
+        /// Length of the decompressed data
+        actual: usize,
+    },
+    /// Compressed stream is corrupt
+    Stream(std::io::Error),
+}
+
+impl core::fmt::Display for DecompressError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Truncated => f.write_str("compressed lump is truncated"),
+            Self::SizeMismatch { expected, actual } => write!(
+                f,
+                "compressed lump decompressed to {actual} bytes instead of {expected}"
+            ),
+            Self::Stream(e) => write!(f, "compressed lump is corrupt: {e}"),
+        }
+    }
+}
+
+impl core::error::Error for DecompressError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Truncated | Self::SizeMismatch { .. } => None,
+            Self::Stream(e) => Some(e),
+        }
+    }
+}
+
+/// Whether `data` starts with an LZMA lump header
+pub(crate) fn is_compressed(data: &[u8]) -> bool {
+    data.starts_with(&LZMA_ID)
+}
+
+/// Decompress data wrapped in an LZMA lump header
+pub(crate) fn decompress(data: &[u8]) -> Result<Vec<u8>, DecompressError> {
+    let (header, stream) =
+        LzmaHeader::read_from_prefix(data).map_err(|_| DecompressError::Truncated)?;
+    let stream = stream
+        .get(..header.lzma_size as usize)
+        .ok_or(DecompressError::Truncated)?;
+
+    let [props, dict_size @ ..] = header.properties;
+    let actual_size = header.actual_size as usize;
+
+    let mut reader = LzmaReader::new_with_props(
+        stream,
+        actual_size as u64,
+        props,
+        u32::from_le_bytes(dict_size),
+        None,
+    )
+    .map_err(DecompressError::Stream)?;
+
+    let mut out = Vec::with_capacity(actual_size.min(stream.len() * MAX_PREALLOC_RATIO));
+    reader
+        .read_to_end(&mut out)
+        .map_err(DecompressError::Stream)?;
+    if out.len() != actual_size {
+        return Err(DecompressError::SizeMismatch {
+            expected: actual_size,
+            actual: out.len(),
+        });
+    }
+    Ok(out)
+}
+
+/// Compress data and wrap it in an LZMA lump header
+pub(crate) fn compress(data: &[u8]) -> std::io::Result<Vec<u8>> {
+    let options = LzmaOptions::default();
+    let mut writer = LzmaWriter::new_no_header(Vec::new(), &options, false)?;
+    writer.write_all(data)?;
+    let props = writer.props();
+    let stream = writer.finish()?;
+
+    let [d0, d1, d2, d3] = options.dict_size.to_le_bytes();
+    let header = LzmaHeader {
+        id: LZMA_ID,
+        actual_size: data.len() as u32,
+        lzma_size: stream.len() as u32,
+        properties: [props, d0, d1, d2, d3],
+    };
+
+    let mut out = Vec::with_capacity(size_of::<LzmaHeader>() + stream.len());
+    out.extend_from_slice(header.as_bytes());
+    out.extend_from_slice(&stream);
+    Ok(out)
+}
+
+/// Selection of lumps to compress when writing
+#[derive(Debug, Clone, Copy)]
+pub enum CompressionPolicy<'p> {
+    /// Write every lump as-is
+    None,
+    /// Compress every eligible lump
+    All,
+    /// Compress only the listed lumps, where eligible
+    Only(&'p [usize]),
+}
+
+impl CompressionPolicy<'_> {
+    /// Whether the lump at `index` should be compressed under this policy
+    ///
+    /// [`LumpId::PakFile`] must remain a raw zip archive, and [`LumpId::GameLump`] is
+    /// compressed per sub-lump rather than as a whole, so neither is ever selected.
+    pub fn selects(&self, index: usize) -> bool {
+        if index == LumpId::PakFile as usize || index == LumpId::GameLump as usize {
+            return false;
+        }
+
+        match self {
+            Self::None => false,
+            Self::All => true,
+            Self::Only(indices) => indices.contains(&index),
+        }
+    }
+}
+
+impl Bsp<'_> {
+    /// Lump bytes, transparently decompressing lumps stored in the Source LZMA container
+    pub fn lump_decompressed<I>(&self, index: I) -> Result<LumpData<'_>, DecompressError>
+    where
+        I: Into<usize>,
+    {
+        let (_, data) = self.lump(index);
+        if is_compressed(&data) {
+            decompress(&data).map(LumpData::Decompressed)
+        } else {
+            Ok(LumpData::Raw(Ref::map(data, |v| &**v)))
+        }
+    }
+
+    /// Write to `writer` like [`Bsp::write_to_io`], compressing the lumps selected by `policy`
+    ///
+    /// Empty and already-compressed lumps are written unchanged. As the engine expects,
+    /// the identifier of each compressed lump is replaced with its uncompressed length.
+    pub fn write_to_io_compressed<W>(
+        &self,
+        writer: W,
+        policy: CompressionPolicy<'_>,
+    ) -> std::io::Result<()>
+    where
+        W: std::io::Write,
+    {
+        let lumps: Vec<_> = self.lump_iter().collect();
+        let lumps = lumps
+            .iter()
+            .enumerate()
+            .map(|(index, (metadata, data))| {
+                let mut metadata = *metadata.borrow().as_ref();
+                if !policy.selects(index) || data.is_empty() || is_compressed(data) {
+                    return Ok((metadata, Cow::Borrowed(data.as_ref())));
+                }
+
+                metadata.identifier = (data.len() as u32).to_le_bytes();
+                Ok((metadata, Cow::Owned(compress(data)?)))
+            })
+            .collect::<std::io::Result<Vec<_>>>()?;
+
+        self.write_lumps(
+            writer,
+            lumps
+                .iter()
+                .map(|(metadata, data)| (*metadata, data.as_ref())),
+            LUMP_ALIGNMENT,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let data = b"lump data lump data lump data".repeat(16);
+        let compressed = compress(&data).unwrap();
+        assert!(is_compressed(&compressed));
+        assert_eq!(decompress(&compressed).unwrap(), data);
+    }
+
+    #[test]
+    fn huge_declared_size_is_not_preallocated() {
+        let header = LzmaHeader {
+            id: LZMA_ID,
+            actual_size: u32::MAX,
+            lzma_size: 0,
+            properties: [0x5d, 0, 0, 1, 0],
+        };
+        let data = header.as_bytes().to_vec();
+        assert_eq!(data.len(), 17);
+        // Fails on the empty stream instead of allocating 4 GiB first
+        assert!(matches!(decompress(&data), Err(DecompressError::Stream(_))));
+    }
+
+    #[test]
+    fn size_disagreeing_with_stream() {
+        let mut compressed = compress(&[7; 64]).unwrap();
+        // Claim one byte less than the stream holds
+        compressed[4..8].copy_from_slice(&63u32.to_le_bytes());
+        assert!(decompress(&compressed).is_err());
+    }
+}
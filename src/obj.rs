@@ -0,0 +1,129 @@
+use alloc::vec::Vec;
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+};
+
+use crate::{geometry::fan_ccw, Bsp, ExportOptions, SurfaceFlags, TexInfo};
+
+impl Bsp<'_> {
+    /// Write the faces of the selected models to `writer` as a Wavefront OBJ mesh
+    ///
+    /// Faces are triangulated like [`Bsp::model_triangles`], but wound counter-clockwise as
+    /// OBJ expects, and positions are written in map units with Z up. Broken geometry is
+    /// reported as [`io::ErrorKind::InvalidData`] wrapping a
+    /// [`ModelGeometryError`](crate::ModelGeometryError).
+    pub fn export_obj<W>(&self, mut writer: W, options: &ExportOptions) -> io::Result<()>
+    where
+        W: Write,
+    {
+        let texinfo = self.texinfo().ok();
+        let flags = |texinfo_index: i16| {
+            usize::try_from(texinfo_index)
+                .ok()
+                .and_then(|index| texinfo.as_ref()?.get(index))
+                .map(TexInfo::surface_flags)
+                .unwrap_or_default()
+        };
+
+        // Welded vertices by the bits of their position, with -0.0 folded into 0.0
+        let mut welded: HashMap<[u32; 3], usize> = HashMap::new();
+        let mut vertex_count = 0;
+
+        for &model in &options.models {
+            let mut polygons = Vec::new();
+            self.model_polygons(model, |face, polygon| {
+                polygons.push((face.texinfo, polygon.to_vec()));
+            })
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+            writeln!(writer, "o model_{model}")?;
+            let mut material = None;
+            for (texinfo_index, polygon) in polygons {
+                if !options.include_nodraw && flags(texinfo_index).contains(SurfaceFlags::NODRAW) {
+                    continue;
+                }
+
+                if options.material_groups && material != Some(texinfo_index) {
+                    material = Some(texinfo_index);
+                    match usize::try_from(texinfo_index)
+                        .ok()
+                        .and_then(|index| self.material_name(index))
+                    {
+                        Some(name) => writeln!(writer, "usemtl {}", &*name)?,
+                        None => writeln!(writer, "usemtl texinfo_{texinfo_index}")?,
+                    }
+                }
+
+                let mut indices = Vec::with_capacity(polygon.len());
+                for position in &polygon {
+                    let key = position.map(|c| (c + 0.0).to_bits());
+                    let index = match welded.get(&key) {
+                        Some(&index) => index,
+                        None => {
+                            let [x, y, z] = position;
+                            writeln!(writer, "v {x} {y} {z}")?;
+                            // OBJ indices start at 1
+                            vertex_count += 1;
+                            if options.weld_vertices {
+                                welded.insert(key, vertex_count);
+                            }
+                            vertex_count
+                        }
+                    };
+                    indices.push(index);
+                }
+
+                for [a, b, c] in fan_ccw(&indices) {
+                    writeln!(writer, "f {a} {b} {c}")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::String, vec};
+
+    use zerocopy::{FromZeros, IntoBytes};
+
+    use crate::{BspBuilder, ExportOptions, Face, LumpId, LumpMetadata, Model, Vertex};
+
+    #[test]
+    fn faces_wind_counter_clockwise() {
+        let metadata = LumpMetadata {
+            version: 0,
+            identifier: [0; 4],
+        };
+        let vertices =
+            [[0.0, 0.0, 0.0], [0.0, 1.0, 0.0], [1.0, 0.0, 0.0]].map(|[x, y, z]| Vertex { x, y, z });
+        let edges: [[u16; 2]; 3] = [[0, 1], [1, 2], [2, 0]];
+        let surfedges: [i32; 3] = [0, 1, 2];
+        let mut face = Face::new_zeroed();
+        face.numedges = 3;
+        face.texinfo = -1;
+        let mut model = Model::new_zeroed();
+        model.numfaces = 1;
+
+        let bsp = BspBuilder::new(*b"VBSP", 20)
+            .set_lump(LumpId::Vertexes, metadata, vertices.as_bytes().to_vec())
+            .set_lump(LumpId::Edges, metadata, edges.as_bytes().to_vec())
+            .set_lump(LumpId::SurfEdges, metadata, surfedges.as_bytes().to_vec())
+            .set_lump(LumpId::Faces, metadata, face.as_bytes().to_vec())
+            .set_lump(LumpId::Models, metadata, model.as_bytes().to_vec())
+            .build();
+
+        let options = ExportOptions {
+            material_groups: false,
+            ..ExportOptions::default()
+        };
+        let mut out = vec![];
+        bsp.export_obj(&mut out, &options).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "o model_0\nv 0 0 0\nv 0 1 0\nv 1 0 0\nf 1 3 2\n"
+        );
+    }
+}
@@ -0,0 +1,175 @@
+use alloc::vec::Vec;
+use core::ops::Deref;
+
+use crate::{Bsp, ParseError};
+
+// Only needed for reading through `std::io`
+#[cfg(feature = "std")]
+use {
+    crate::{Header, LumpCell, LUMP_DEF_COUNT},
+    alloc::borrow::Cow,
+    std::io::{self, Read, Seek, SeekFrom},
+    zerocopy::{FromZeros, IntoBytes},
+};
+
+/// [`Bsp`] bundled with the buffer it was parsed from
+///
+/// The buffer is a `Vec<u8>` by default, or a memory map for [`MmapBsp`].
+///
+/// This can't implement `Deref<Target = Bsp>`: [`Bsp`] is invariant over its lifetime
+/// since lumps live in a `RefCell`, so handing out a `&Bsp<'static>` would let a
+/// borrowed lump escape (or a shorter-lived one be stored) past the buffer's lifetime.
+/// Access goes through [`OwnedBsp::with`] instead, whose closure can't name the lifetime.
+pub struct OwnedBsp<B = Vec<u8>>
+where
+    B: Deref<Target = [u8]>,
+{
+    // Declared before `data` so that it's dropped first
+    bsp: Bsp<'static>,
+    // Never mutated while `bsp` is alive. Moving the buffer doesn't move the bytes it
+    // points to, which is all `bsp` borrows.
+    data: B,
+}
+
+/// [`Bsp`] parsed in place from a memory-mapped file, see [`Bsp::from_path_mmap`]
+#[cfg(feature = "mmap")]
+pub type MmapBsp = OwnedBsp<memmap2::Mmap>;
+
+impl OwnedBsp {
+    /// Take ownership of `data` and parse it
+    pub fn from_vec(data: Vec<u8>) -> Result<Self, ParseError> {
+        // SAFETY: moving a `Vec` doesn't move its heap allocation
+        unsafe { Self::from_buffer(data) }
+    }
+}
+
+impl<B> OwnedBsp<B>
+where
+    B: Deref<Target = [u8]>,
+{
+    /// Take ownership of `data` and parse it
+    ///
+    /// # Safety
+    ///
+    /// The slice `data` derefs to must stay at the same address and unchanged when
+    /// `data` is moved, for as long as it's alive.
+    unsafe fn from_buffer(data: B) -> Result<Self, ParseError> {
+        // SAFETY: the slice stays valid until `data` is dropped, which happens after
+        // `bsp`, and `bsp` is only exposed under a higher-ranked lifetime so no borrow
+        // of it can outlive `self`
+        let slice: &'static [u8] =
+            unsafe { core::slice::from_raw_parts(data.as_ptr(), data.len()) };
+        let bsp = Bsp::parse(slice)?;
+        Ok(Self { bsp, data })
+    }
+
+    /// Call `f` with the parsed [`Bsp`]
+    pub fn with<R, F>(&self, f: F) -> R
+    where
+        F: for<'b> FnOnce(&Bsp<'b>) -> R,
+    {
+        f(&self.bsp)
+    }
+
+    /// Buffer the [`Bsp`] was parsed from, without any lump edits applied
+    ///
+    /// Empty if it was read with [`Bsp::parse_reader`].
+    pub fn source(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl<B> core::fmt::Debug for OwnedBsp<B>
+where
+    B: Deref<Target = [u8]>,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.bsp.fmt(f)
+    }
+}
+
+impl Bsp<'_> {
+    /// Read a BSP file from `reader`, seeking to each lump and reading it into its own
+    /// buffer
+    ///
+    /// Lump offsets are relative to the start of the stream. Only the header and lump
+    /// data are read, so padding and unreferenced bytes never hit memory, but every lump
+    /// is loaded up front. Unlike [`Bsp::parse`], which borrows from a buffer holding the
+    /// whole file, the result owns one allocation per lump and [`OwnedBsp::source`] is
+    /// empty. Malformed headers are reported as [`io::ErrorKind::InvalidData`] wrapping a
+    /// [`ParseError`].
+    #[cfg(feature = "std")]
+    pub fn parse_reader<R: Read + Seek>(mut reader: R) -> io::Result<OwnedBsp> {
+        let invalid = |e: ParseError| io::Error::new(io::ErrorKind::InvalidData, e);
+
+        let file_len = usize::try_from(reader.seek(SeekFrom::End(0))?).unwrap_or(usize::MAX);
+        if file_len < size_of::<Header>() {
+            return Err(invalid(ParseError::HeaderTooShort));
+        }
+
+        let mut header = Header::new_zeroed();
+        reader.seek(SeekFrom::Start(0))?;
+        reader.read_exact(header.as_mut_bytes())?;
+
+        let ranges = Self::lump_ranges(&header, file_len).map_err(invalid)?;
+        let mut buffers: [Vec<u8>; LUMP_DEF_COUNT] = core::array::from_fn(|_| Vec::new());
+        for (range, buffer) in ranges.iter().zip(&mut buffers) {
+            if range.is_empty() {
+                continue;
+            }
+            // Ranges are relative to the end of the header
+            reader.seek(SeekFrom::Start((size_of::<Header>() + range.start) as u64))?;
+            buffer.resize(range.len(), 0);
+            reader.read_exact(buffer)?;
+        }
+
+        let lumps = core::array::from_fn(|i| {
+            LumpCell::new(
+                Cow::Owned(header.lump_defs[i].metadata()),
+                Cow::Owned(core::mem::take(&mut buffers[i])),
+            )
+        });
+        let bsp = Bsp {
+            header: Cow::Owned(header),
+            lumps,
+        };
+        Ok(OwnedBsp {
+            bsp,
+            data: Vec::new(),
+        })
+    }
+
+    /// Read all of `reader` into a buffer and parse it in place
+    ///
+    /// Unlike [`Bsp::parse_reader`] this doesn't need [`Seek`], at the cost of holding
+    /// the whole file in memory, padding included. The buffer is kept as
+    /// [`OwnedBsp::source`]. Malformed headers are reported as
+    /// [`io::ErrorKind::InvalidData`] wrapping a [`ParseError`].
+    #[cfg(feature = "std")]
+    pub fn read_from<R: Read>(mut reader: R) -> io::Result<OwnedBsp> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        OwnedBsp::from_vec(data).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Memory-map the file at `path` and parse it in place
+    ///
+    /// Lumps borrow straight from the mapping, so nothing but the [`Bsp`] itself is
+    /// allocated and pages are only loaded as lumps are read. Malformed headers are
+    /// reported as [`io::ErrorKind::InvalidData`] wrapping a [`ParseError`].
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated while the returned [`MmapBsp`] is
+    /// alive, by this or any other process. See [`memmap2::Mmap::map`].
+    #[cfg(feature = "mmap")]
+    pub unsafe fn from_path_mmap(path: impl AsRef<std::path::Path>) -> io::Result<MmapBsp> {
+        let file = std::fs::File::open(path)?;
+        // SAFETY: upheld by the caller
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+        // SAFETY: the mapping stays at the same address until it's dropped, and the
+        // caller guarantees the file isn't modified
+        unsafe { OwnedBsp::from_buffer(map) }
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
@@ -0,0 +1,100 @@
+use core::cell::Ref;
+
+#[cfg(feature = "zip")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "zip")]
+use crate::LumpMetadata;
+use crate::{Bsp, LumpId};
+
+impl Bsp<'_> {
+    /// Bytes of [`LumpId::PakFile`], a zip archive of files embedded in the map
+    pub fn pakfile(&self) -> Ref<'_, [u8]> {
+        let (_, data) = self.lump(LumpId::PakFile);
+        Ref::map(data, |v| &**v)
+    }
+
+    /// Open [`LumpId::PakFile`] as a zip archive
+    ///
+    /// Entries inside the archive are normally stored without compression, but the lump
+    /// itself may be LZMA-wrapped by some compilers. With the `lzma` feature enabled such
+    /// lumps are decompressed first; otherwise opening them fails as an invalid archive.
+    #[cfg(feature = "zip")]
+    pub fn pakfile_archive(
+        &self,
+    ) -> zip::result::ZipResult<zip::ZipArchive<std::io::Cursor<crate::LumpData<'_>>>> {
+        #[cfg(feature = "lzma")]
+        let data = self
+            .lump_decompressed(LumpId::PakFile)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        #[cfg(not(feature = "lzma"))]
+        let data = crate::LumpData::Raw(self.pakfile());
+
+        zip::ZipArchive::new(std::io::Cursor::new(data))
+    }
+
+    /// Embed `data` in [`LumpId::PakFile`] at `path`, replacing any entry already there
+    ///
+    /// This is `bspzip -addlist` for a single file. The new entry is stored without
+    /// compression, as the engine expects. Other entries are copied over unchanged, and
+    /// the lump is written back without LZMA compression. `path` is matched exactly, so
+    /// use forward slashes and the same case as the existing entries.
+    #[cfg(feature = "zip")]
+    pub fn add_pakfile_file(&mut self, path: &str, data: &[u8]) -> zip::result::ZipResult<()> {
+        use std::io::Write;
+
+        self.rewrite_pakfile(path, |writer| {
+            let options = zip::write::SimpleFileOptions::default()
+                .compression_method(zip::CompressionMethod::Stored);
+            writer.start_file(path, options)?;
+            writer.write_all(data)?;
+            Ok(())
+        })?;
+        Ok(())
+    }
+
+    /// Remove the entry at `path` from [`LumpId::PakFile`], returning whether there was one
+    ///
+    /// Other entries are kept like [`Bsp::add_pakfile_file`] does. The lump is left
+    /// untouched if nothing matches.
+    #[cfg(feature = "zip")]
+    pub fn remove_pakfile_file(&mut self, path: &str) -> zip::result::ZipResult<bool> {
+        if self.pakfile().is_empty() || self.pakfile_archive()?.index_for_name(path).is_none() {
+            return Ok(false);
+        }
+        self.rewrite_pakfile(path, |_| Ok(()))
+    }
+
+    /// Rebuild [`LumpId::PakFile`] from all of its entries except `path`, letting `f`
+    /// append new ones, and return whether `path` was skipped
+    #[cfg(feature = "zip")]
+    fn rewrite_pakfile<F>(&mut self, path: &str, f: F) -> zip::result::ZipResult<bool>
+    where
+        F: FnOnce(&mut zip::ZipWriter<std::io::Cursor<Vec<u8>>>) -> zip::result::ZipResult<()>,
+    {
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        let mut skipped = false;
+        // An empty lump is a map without embedded files rather than a broken archive
+        if !self.pakfile().is_empty() {
+            let mut archive = self.pakfile_archive()?;
+            for index in 0..archive.len() {
+                let file = archive.by_index_raw(index)?;
+                if file.name_raw() == path.as_bytes() {
+                    skipped = true;
+                    continue;
+                }
+                writer.raw_copy_file(file)?;
+            }
+        }
+        f(&mut writer)?;
+        let data = writer.finish()?.into_inner();
+
+        // The identifier holds the uncompressed length of LZMA lumps, and this one isn't
+        let metadata = LumpMetadata {
+            identifier: [0; 4],
+            ..*self.lump(LumpId::PakFile).0.as_ref()
+        };
+        self.replace_lump(LumpId::PakFile, metadata, data);
+        Ok(skipped)
+    }
+}
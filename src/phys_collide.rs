@@ -0,0 +1,193 @@
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use zerocopy::FromBytes;
+use zerocopy_derive::*;
+
+use crate::{Bsp, LumpId};
+
+/// Physics model header as stored in [`LumpId::PhysCollide`] (`dphysmodel_t`)
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Debug, Clone, Copy)]
+#[repr(C)]
+struct RawPhysModel {
+    model_index: i32,
+    data_size: i32,
+    keydata_size: i32,
+    solid_count: i32,
+}
+
+/// Collision model of a brush model in [`LumpId::PhysCollide`]
+///
+/// Ranges index into the lump's data. The solids are left in the engine's opaque
+/// VPhysics format, each prefixed with its length as an `i32`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhysModel {
+    /// Index into [`LumpId::Models`]
+    pub model_index: i32,
+    /// Number of solids in `data`
+    pub solid_count: i32,
+    /// Byte range of the collision solids
+    pub data: Range<usize>,
+    /// Byte range of the keyvalue text describing the solids
+    pub keydata: Range<usize>,
+}
+
+/// Error returned when reading [`LumpId::PhysCollide`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PhysModelError {
+    /// Lump ends in the middle of a model header
+    Truncated,
+    /// Model data extends past the end of the lump
+    OutOfBounds {
+        /// Index into [`LumpId::Models`]
+        model_index: i32,
+    },
+}
+
+impl core::fmt::Display for PhysModelError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Truncated => f.write_str("physics collision lump is truncated"),
+            Self::OutOfBounds { model_index } => write!(
+                f,
+                "physics model of model {model_index} extends past the end of the lump"
+            ),
+        }
+    }
+}
+
+impl core::error::Error for PhysModelError {}
+
+/// Size in the [`LumpId::PhysDisp`] table of a displacement without collision
+const NO_DISP_COLLISION: u16 = 0xFFFF;
+
+/// Collision data of every displacement in [`LumpId::PhysDisp`]
+///
+/// The data is left in the engine's opaque VPhysics format.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PhysDisp {
+    /// Byte range into the lump of each displacement's collision data, in
+    /// [`LumpId::DispInfo`] order, or `None` for displacements without collision
+    pub disps: Vec<Option<Range<usize>>>,
+}
+
+impl PhysDisp {
+    /// Number of displacements in the table
+    pub fn len(&self) -> usize {
+        self.disps.len()
+    }
+
+    /// Whether the table is empty
+    pub fn is_empty(&self) -> bool {
+        self.disps.is_empty()
+    }
+}
+
+/// Error returned when reading [`LumpId::PhysDisp`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PhysDispError {
+    /// Lump ends in the middle of the size table
+    Truncated,
+    /// Collision data extends past the end of the lump
+    OutOfBounds {
+        /// Index into [`LumpId::DispInfo`]
+        disp_index: usize,
+    },
+}
+
+impl core::fmt::Display for PhysDispError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Truncated => f.write_str("displacement collision lump is truncated"),
+            Self::OutOfBounds { disp_index } => write!(
+                f,
+                "collision of displacement {disp_index} extends past the end of the lump"
+            ),
+        }
+    }
+}
+
+impl core::error::Error for PhysDispError {}
+
+impl Bsp<'_> {
+    /// Parse the model headers of [`LumpId::PhysCollide`]
+    ///
+    /// Models are read up to the terminating header with a model index of -1, or the
+    /// end of the lump if it has none.
+    pub fn phys_models(&self) -> Result<Vec<PhysModel>, PhysModelError> {
+        let (_, data) = self.lump(LumpId::PhysCollide);
+
+        let mut models = Vec::new();
+        let mut offset = 0;
+        while offset < data.len() {
+            // Headers are copied out since model sizes needn't keep them aligned
+            let (raw, _) = RawPhysModel::read_from_prefix(&data[offset..])
+                .map_err(|_| PhysModelError::Truncated)?;
+            if raw.model_index == -1 {
+                break;
+            }
+            offset += size_of::<RawPhysModel>();
+
+            let out_of_bounds = PhysModelError::OutOfBounds {
+                model_index: raw.model_index,
+            };
+            let mut take = |size: i32| {
+                let range = usize::try_from(size)
+                    .ok()
+                    .and_then(|size| Some(offset..offset.checked_add(size)?))
+                    .filter(|range| range.end <= data.len())
+                    .ok_or(out_of_bounds.clone())?;
+                offset = range.end;
+                Ok(range)
+            };
+            let model_data = take(raw.data_size)?;
+            let keydata = take(raw.keydata_size)?;
+
+            models.push(PhysModel {
+                model_index: raw.model_index,
+                solid_count: raw.solid_count,
+                data: model_data,
+                keydata,
+            });
+        }
+        Ok(models)
+    }
+
+    /// Parse the size table of [`LumpId::PhysDisp`] into the byte range of each
+    /// displacement's collision data
+    ///
+    /// The lump starts with a `u16` displacement count and a `u16` size per
+    /// displacement, followed by the data back to back. An empty lump has no
+    /// displacements.
+    pub fn phys_disp(&self) -> Result<PhysDisp, PhysDispError> {
+        let (_, data) = self.lump(LumpId::PhysDisp);
+        if data.is_empty() {
+            return Ok(PhysDisp::default());
+        }
+
+        let (count, rest) = u16::read_from_prefix(&data).map_err(|_| PhysDispError::Truncated)?;
+        // Read rather than cast, since the table needn't be aligned
+        let sizes = rest
+            .get(..usize::from(count) * size_of::<u16>())
+            .ok_or(PhysDispError::Truncated)?;
+
+        let mut offset = size_of::<u16>() + sizes.len();
+        let disps = sizes
+            .chunks_exact(size_of::<u16>())
+            .map(|size| u16::from_le_bytes([size[0], size[1]]))
+            .enumerate()
+            .map(|(disp_index, size)| {
+                if size == NO_DISP_COLLISION {
+                    return Ok(None);
+                }
+                let range = offset..offset + usize::from(size);
+                if range.end > data.len() {
+                    return Err(PhysDispError::OutOfBounds { disp_index });
+                }
+                offset = range.end;
+                Ok(Some(range))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(PhysDisp { disps })
+    }
+}
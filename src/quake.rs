@@ -0,0 +1,98 @@
+use zerocopy_derive::*;
+
+use crate::{
+    sealed::{Directory, LumpEntry},
+    BspFile, BspFlavor, LumpMetadata,
+};
+
+/// Lump definition count of Quake and GoldSrc files
+pub const QUAKE_LUMP_DEF_COUNT: usize = 15;
+
+/// Quake/GoldSrc lump definition
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Debug, Clone, Copy)]
+#[repr(C)]
+pub struct QuakeLumpDef {
+    /// Absolute offset in file
+    offset: u32,
+    /// Length of data
+    length: u32,
+}
+
+impl QuakeLumpDef {
+    /// Absolute offset in file
+    pub fn offset(&self) -> u32 {
+        self.offset
+    }
+
+    /// Length of data
+    pub fn length(&self) -> u32 {
+        self.length
+    }
+}
+
+impl LumpEntry for QuakeLumpDef {
+    fn offset(&self) -> u32 {
+        self.offset
+    }
+
+    fn length(&self) -> u32 {
+        self.length
+    }
+
+    fn metadata(&self) -> Option<&LumpMetadata> {
+        None
+    }
+
+    fn set_range(&mut self, offset: u32, length: u32) {
+        self.offset = offset;
+        self.length = length;
+    }
+
+    fn set_metadata(&mut self, _: LumpMetadata) {}
+}
+
+/// Quake (version 29) and GoldSrc (version 30) BSP file header
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Debug, Clone)]
+#[repr(C)]
+pub struct QuakeHeader {
+    /// File format version, which doubles as the file magic
+    pub version: u32,
+    /// Lump definitions
+    lump_defs: [QuakeLumpDef; QUAKE_LUMP_DEF_COUNT],
+}
+
+impl Directory<QUAKE_LUMP_DEF_COUNT> for QuakeHeader {
+    type LumpDef = QuakeLumpDef;
+
+    const FLAVORS: &'static [BspFlavor] = &[BspFlavor::Quake, BspFlavor::GoldSrc];
+
+    fn magic(&self) -> [u8; 4] {
+        self.version.to_le_bytes()
+    }
+
+    fn directory(&self) -> &[QuakeLumpDef; QUAKE_LUMP_DEF_COUNT] {
+        &self.lump_defs
+    }
+
+    fn directory_mut(&mut self) -> &mut [QuakeLumpDef; QUAKE_LUMP_DEF_COUNT] {
+        &mut self.lump_defs
+    }
+}
+
+/// Quake or GoldSrc BSP file
+///
+/// These formats store no per-lump metadata, so every lump reports zeroed
+/// [`LumpMetadata`] and changes to it aren't written.
+pub type QuakeBsp<'a> = BspFile<'a, QuakeHeader, QUAKE_LUMP_DEF_COUNT>;
+
+/// GoldSrc BSP file, which shares its layout with [`QuakeBsp`]
+pub type GoldSrcBsp<'a> = QuakeBsp<'a>;
+
+impl core::fmt::Debug for QuakeBsp<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("QuakeBsp")
+            .field("version", &self.header.version)
+            // Indicate that we have omitted data (lump entries)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,57 @@
+use zerocopy_derive::*;
+
+use crate::{sealed::Directory, BspFile, BspFlavor, LumpDef};
+
+/// Lump definition count of `rBSP` files
+pub const RESPAWN_LUMP_DEF_COUNT: usize = 128;
+
+/// Respawn (Titanfall/Apex) BSP file header
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Debug, Clone)]
+#[repr(C)]
+pub struct RespawnHeader {
+    /// File format identifier (`rBSP`)
+    pub identifier: [u8; 4],
+    /// File format version
+    pub version: u32,
+    /// File revision
+    pub revision: i32,
+    /// Index of the last lump, always 127
+    pub last_lump: u32,
+    /// Lump definitions
+    lump_defs: [LumpDef; RESPAWN_LUMP_DEF_COUNT],
+}
+
+impl Directory<RESPAWN_LUMP_DEF_COUNT> for RespawnHeader {
+    type LumpDef = LumpDef;
+
+    const FLAVORS: &'static [BspFlavor] = &[BspFlavor::Respawn];
+
+    fn magic(&self) -> [u8; 4] {
+        self.identifier
+    }
+
+    fn directory(&self) -> &[LumpDef; RESPAWN_LUMP_DEF_COUNT] {
+        &self.lump_defs
+    }
+
+    fn directory_mut(&mut self) -> &mut [LumpDef; RESPAWN_LUMP_DEF_COUNT] {
+        &mut self.lump_defs
+    }
+}
+
+/// Respawn (Titanfall/Apex) BSP file
+///
+/// Lumps stored in external `.bsp_lump` files aren't resolved, so only lumps contained
+/// in the `.bsp` itself are available.
+pub type RespawnBsp<'a> = BspFile<'a, RespawnHeader, RESPAWN_LUMP_DEF_COUNT>;
+
+impl core::fmt::Debug for RespawnBsp<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("RespawnBsp")
+            .field("identifier", &self.header.identifier)
+            .field("version", &self.header.version)
+            .field("revision", &self.header.revision)
+            // Indicate that we have omitted data (lump entries)
+            .finish_non_exhaustive()
+    }
+}
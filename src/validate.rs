@@ -0,0 +1,278 @@
+use alloc::{vec, vec::Vec};
+
+use crate::{Bsp, LumpId, ParseError};
+
+/// Checks performed by [`Bsp::parse_validated`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValidationOptions {
+    /// Check that surfedges refer to existing edges
+    pub surfedges: bool,
+    /// Check that faces refer to existing surfedges and texinfos
+    pub faces: bool,
+    /// Check that no two lumps share bytes in the file
+    pub overlaps: bool,
+}
+
+impl Default for ValidationOptions {
+    fn default() -> Self {
+        Self {
+            surfedges: true,
+            faces: true,
+            overlaps: true,
+        }
+    }
+}
+
+/// Problem found by [`Bsp::parse_validated`]
+#[derive(Debug)]
+pub enum ValidationIssue {
+    /// File couldn't be parsed at all
+    Parse(ParseError),
+    /// Lump length isn't a multiple of its record size, or the lump is misaligned
+    MalformedLump {
+        /// Lump index
+        index: usize,
+    },
+    /// Surfedge refers to an edge past the end of [`LumpId::Edges`]
+    SurfEdgeOutOfRange {
+        /// Surfedge index
+        surfedge: usize,
+        /// Signed edge index stored in the surfedge
+        edge: i32,
+        /// Number of edges
+        edge_count: usize,
+    },
+    /// Face surfedges lie outside of [`LumpId::SurfEdges`]
+    FaceEdgesOutOfRange {
+        /// Face index
+        face: usize,
+        /// First surfedge of the face
+        firstedge: i32,
+        /// Number of surfedges of the face
+        numedges: i16,
+        /// Number of surfedges
+        surfedge_count: usize,
+    },
+    /// Face refers to a texinfo past the end of [`LumpId::TexInfo`]
+    FaceTexInfoOutOfRange {
+        /// Face index
+        face: usize,
+        /// Texinfo index of the face
+        texinfo: i16,
+        /// Number of texinfos
+        texinfo_count: usize,
+    },
+    /// Two lumps share bytes in the file
+    LumpOverlap {
+        /// Lower lump index
+        a: usize,
+        /// Higher lump index
+        b: usize,
+    },
+}
+
+impl core::fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Parse(e) => e.fmt(f),
+            Self::MalformedLump { index } => write!(f, "lump {index} is malformed"),
+            Self::SurfEdgeOutOfRange {
+                surfedge,
+                edge,
+                edge_count,
+            } => write!(
+                f,
+                "surfedge {surfedge} refers to edge {edge} but there are {edge_count} edges"
+            ),
+            Self::FaceEdgesOutOfRange {
+                face,
+                firstedge,
+                numedges,
+                surfedge_count,
+            } => write!(
+                f,
+                "face {face} uses surfedges {firstedge}..{firstedge}+{numedges} but there are {surfedge_count} surfedges"
+            ),
+            Self::FaceTexInfoOutOfRange {
+                face,
+                texinfo,
+                texinfo_count,
+            } => write!(
+                f,
+                "face {face} refers to texinfo {texinfo} but there are {texinfo_count} texinfos"
+            ),
+            Self::LumpOverlap { a, b } => write!(f, "lumps {a} and {b} overlap"),
+        }
+    }
+}
+
+/// Every problem found by [`Bsp::parse_validated`]
+#[derive(Debug, Default)]
+pub struct ValidationReport {
+    /// Problems in the order they were found
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    /// Whether no problems were found
+    pub fn is_empty(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl core::fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} validation issue(s)", self.issues.len())?;
+        for issue in &self.issues {
+            write!(f, "\n- {issue}")?;
+        }
+        Ok(())
+    }
+}
+
+impl core::error::Error for ValidationReport {}
+
+impl<'a> Bsp<'a> {
+    /// Parse like [`Bsp::parse`], then check cross-lump invariants selected by `opts`
+    ///
+    /// All problems are collected into the returned [`ValidationReport`] rather than
+    /// stopping at the first one.
+    pub fn parse_validated(
+        data: &'a [u8],
+        opts: ValidationOptions,
+    ) -> Result<Self, ValidationReport> {
+        let bsp = Self::parse(data).map_err(|e| ValidationReport {
+            issues: vec![ValidationIssue::Parse(e)],
+        })?;
+
+        let report = bsp.validate(opts);
+        if report.is_empty() {
+            Ok(bsp)
+        } else {
+            Err(report)
+        }
+    }
+
+    /// Check cross-lump invariants selected by `opts`
+    pub fn validate(&self, opts: ValidationOptions) -> ValidationReport {
+        let mut issues = Vec::new();
+        if opts.surfedges {
+            self.validate_surfedges(&mut issues);
+        }
+        if opts.faces {
+            self.validate_faces(&mut issues);
+        }
+        if opts.overlaps {
+            self.validate_overlaps(&mut issues);
+        }
+        ValidationReport { issues }
+    }
+
+    fn validate_surfedges(&self, issues: &mut Vec<ValidationIssue>) {
+        let surfedges = self.surfedges();
+        let edges = self.edges();
+        report_malformed(issues, LumpId::SurfEdges, surfedges.is_err());
+        report_malformed(issues, LumpId::Edges, edges.is_err());
+        let (Ok(surfedges), Ok(edges)) = (surfedges, edges) else {
+            return;
+        };
+
+        for (surfedge, &edge) in surfedges.iter().enumerate() {
+            if edge.unsigned_abs() as usize >= edges.len() {
+                issues.push(ValidationIssue::SurfEdgeOutOfRange {
+                    surfedge,
+                    edge,
+                    edge_count: edges.len(),
+                });
+            }
+        }
+    }
+
+    fn validate_faces(&self, issues: &mut Vec<ValidationIssue>) {
+        let faces = self.faces();
+        let surfedges = self.surfedges();
+        let texinfo = self.texinfo();
+        report_malformed(issues, LumpId::Faces, faces.is_err());
+        report_malformed(issues, LumpId::SurfEdges, surfedges.is_err());
+        report_malformed(issues, LumpId::TexInfo, texinfo.is_err());
+        let (Ok(faces), Ok(surfedges), Ok(texinfo)) = (faces, surfedges, texinfo) else {
+            return;
+        };
+        let texinfo_count = texinfo.len();
+
+        for (index, face) in faces.iter().enumerate() {
+            let edges_fit = usize::try_from(face.firstedge)
+                .ok()
+                .zip(usize::try_from(face.numedges).ok())
+                .is_some_and(|(first, len)| first + len <= surfedges.len());
+            if !edges_fit {
+                issues.push(ValidationIssue::FaceEdgesOutOfRange {
+                    face: index,
+                    firstedge: face.firstedge,
+                    numedges: face.numedges,
+                    surfedge_count: surfedges.len(),
+                });
+            }
+
+            if usize::try_from(face.texinfo).map_or(true, |t| t >= texinfo_count) {
+                issues.push(ValidationIssue::FaceTexInfoOutOfRange {
+                    face: index,
+                    texinfo: face.texinfo,
+                    texinfo_count,
+                });
+            }
+        }
+    }
+
+    fn validate_overlaps(&self, issues: &mut Vec<ValidationIssue>) {
+        issues.extend(
+            self.lump_overlaps()
+                .into_iter()
+                .map(|(a, b)| ValidationIssue::LumpOverlap { a, b }),
+        );
+    }
+
+    /// Pairs of lump indices whose byte ranges in the file intersect
+    ///
+    /// Zero-length lumps are ignored. Each pair is ordered with the lower index first,
+    /// and the pairs are sorted.
+    pub fn lump_overlaps(&self) -> Vec<(usize, usize)> {
+        let mut ranges: Vec<_> = self
+            .header
+            .lump_defs
+            .iter()
+            .enumerate()
+            .filter(|(_, def)| def.length != 0)
+            .map(|(index, def)| {
+                let start = u64::from(def.offset);
+                (index, start..start + u64::from(def.length))
+            })
+            .collect();
+        ranges.sort_by_key(|(_, range)| range.start);
+
+        let mut overlaps = Vec::new();
+        for (i, (a, range)) in ranges.iter().enumerate() {
+            // Ranges are sorted by start, so only the following ranges that start before
+            // this one ends can intersect it
+            for (b, _) in ranges[i + 1..]
+                .iter()
+                .take_while(|(_, other)| other.start < range.end)
+            {
+                overlaps.push((*a.min(b), *a.max(b)));
+            }
+        }
+        overlaps.sort_unstable();
+        overlaps
+    }
+}
+
+/// Record that `lump` is malformed, once
+fn report_malformed(issues: &mut Vec<ValidationIssue>, lump: LumpId, malformed: bool) {
+    let index = lump as usize;
+    let reported = issues
+        .iter()
+        .any(|issue| matches!(issue, ValidationIssue::MalformedLump { index: i } if *i == index));
+    if malformed && !reported {
+        issues.push(ValidationIssue::MalformedLump { index });
+    }
+}
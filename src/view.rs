@@ -0,0 +1,194 @@
+use zerocopy::{ConvertError, FromBytes, Immutable, KnownLayout};
+
+use crate::{
+    aligned_empty, cast_prefix, sealed::LumpEntry, BspFile, BspHeader, Header, LumpCastError,
+    LumpCastErrorKind, LumpMetadata, ParseError, TrailingPolicy, LUMP_DEF_COUNT,
+};
+
+/// Metadata of lumps in formats that don't store any
+static ZEROED_METADATA: LumpMetadata = LumpMetadata {
+    version: 0,
+    identifier: [0; 4],
+};
+
+/// Read-only representation of a BSP file with header `H` and `N` lumps
+///
+/// Unlike [`BspFile`] this holds plain references instead of `RefCell`s, so it's `Sync`
+/// and lumps are read without any borrow bookkeeping. There is no way to edit it.
+#[derive(Debug)]
+pub struct BspFileView<'a, H, const N: usize>
+where
+    H: BspHeader<N>,
+{
+    /// BSP Header
+    pub header: &'a H,
+    lumps: [(&'a LumpMetadata, &'a [u8]); N],
+}
+
+// Derived impls would needlessly require `H: Copy`
+impl<H, const N: usize> Clone for BspFileView<'_, H, N>
+where
+    H: BspHeader<N>,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<H, const N: usize> Copy for BspFileView<'_, H, N> where H: BspHeader<N> {}
+
+/// Read-only Source engine BSP file
+pub type BspView<'a> = BspFileView<'a, Header, LUMP_DEF_COUNT>;
+
+const _: () = {
+    const fn assert_sync<T: Send + Sync>() {}
+    assert_sync::<BspView<'static>>();
+};
+
+impl<'a, H, const N: usize> BspFileView<'a, H, N>
+where
+    H: BspHeader<N>,
+{
+    /// Parse a BSP file, borrowing the header and lumps from `data`
+    ///
+    /// Malformed input is reported like [`BspFile::parse`].
+    pub fn parse(data: &'a [u8]) -> Result<Self, ParseError> {
+        let file_len = data.len();
+        let (header, data) = H::ref_from_prefix(data).map_err(|e| match e {
+            ConvertError::Alignment(_) => ParseError::HeaderMisaligned,
+            ConvertError::Size(_) => ParseError::HeaderTooShort,
+            ConvertError::Validity(v) => match v {},
+        })?;
+
+        let ranges = BspFile::<H, N>::lump_ranges(header, file_len)?;
+        let lumps = core::array::from_fn(|i| {
+            let metadata = header.directory()[i].metadata().unwrap_or(&ZEROED_METADATA);
+            // Ranges were checked against the file length above
+            (metadata, &data[ranges[i].clone()])
+        });
+
+        Ok(Self { header, lumps })
+    }
+
+    /// Metadata and data of the lump at `index`
+    ///
+    /// Panics if `index` is out of range.
+    pub fn lump<I>(&self, index: I) -> (&'a LumpMetadata, &'a [u8])
+    where
+        I: Into<usize>,
+    {
+        self.lumps[index.into()]
+    }
+
+    /// Like [`BspFileView::lump`], but returns `None` if `index` is out of range
+    pub fn try_lump<I>(&self, index: I) -> Option<(&'a LumpMetadata, &'a [u8])>
+    where
+        I: Into<usize>,
+    {
+        self.lumps.get(index.into()).copied()
+    }
+
+    /// Data of the lump at `index`, without its metadata
+    ///
+    /// Panics if `index` is out of range.
+    pub fn raw<I>(&self, index: I) -> &'a [u8]
+    where
+        I: Into<usize>,
+    {
+        self.lump(index).1
+    }
+
+    /// Cast the lump at `index` to `T`, like [`Bsp::lump_cast`](crate::Bsp::lump_cast)
+    ///
+    /// Panics if `index` is out of range.
+    pub fn lump_cast<T, I>(&self, index: I) -> Result<&'a T, LumpCastError<T>>
+    where
+        T: ?Sized + FromBytes + KnownLayout + Immutable,
+        I: Into<usize>,
+    {
+        let index = index.into();
+        let data = self.raw(index);
+        let bytes = if data.is_empty() {
+            aligned_empty()
+        } else {
+            data
+        };
+        T::ref_from_bytes(bytes)
+            .map_err(|e| LumpCastError::new(index, data.len(), LumpCastErrorKind::from_convert(&e)))
+    }
+
+    /// Cast the lump at `index` to a slice of `T`
+    ///
+    /// Shorthand for `lump_cast::<[T], _>`.
+    pub fn lump_cast_slice<T, I>(&self, index: I) -> Result<&'a [T], LumpCastError<[T]>>
+    where
+        T: FromBytes + KnownLayout + Immutable,
+        I: Into<usize>,
+    {
+        self.lump_cast(index)
+    }
+
+    /// Cast the lump at `index` to exactly one `T`, like [`Bsp::lump_one`](crate::Bsp::lump_one)
+    pub fn lump_one<T, I>(&self, index: I) -> Result<&'a T, LumpCastError<T>>
+    where
+        T: FromBytes + KnownLayout + Immutable,
+        I: Into<usize>,
+    {
+        self.lump_cast(index)
+    }
+
+    /// Cast the lump at `index` to a slice of any number of `T`, like
+    /// [`Bsp::lump_many`](crate::Bsp::lump_many)
+    pub fn lump_many<T, I>(&self, index: I) -> Result<&'a [T], LumpCastError<[T]>>
+    where
+        T: FromBytes + KnownLayout + Immutable,
+        I: Into<usize>,
+    {
+        self.lump_cast(index)
+    }
+
+    /// Cast the lump at `index` to a slice of `T`, handling trailing bytes per `policy`,
+    /// like [`Bsp::lump_many_with`](crate::Bsp::lump_many_with)
+    pub fn lump_many_with<T, I>(
+        &self,
+        index: I,
+        policy: TrailingPolicy,
+    ) -> Result<&'a [T], LumpCastError<[T]>>
+    where
+        T: FromBytes + KnownLayout + Immutable,
+        I: Into<usize>,
+    {
+        let index = index.into();
+        match policy {
+            TrailingPolicy::Strict => self.lump_cast(index),
+            TrailingPolicy::IgnoreRemainder => {
+                let data = self.raw(index);
+                cast_prefix(data).map_err(|kind| LumpCastError::new(index, data.len(), kind))
+            }
+        }
+    }
+
+    /// Iterate over the metadata and data of every lump in index order
+    pub fn lumps_iter(&self) -> impl Iterator<Item = (usize, (&'a LumpMetadata, &'a [u8]))> {
+        self.lumps.into_iter().enumerate()
+    }
+}
+
+impl<'a, H, const N: usize> BspFile<'a, H, N>
+where
+    H: BspHeader<N>,
+{
+    /// Read-only view of the header and current lumps, edits included
+    ///
+    /// This takes `&mut self` so that no lump can be edited or borrowed mutably while the
+    /// view is alive, which is what lets it hand out plain references.
+    pub fn view(&mut self) -> BspFileView<'_, H, N> {
+        BspFileView {
+            header: &self.header,
+            lumps: self.lumps.each_mut().map(|cell| {
+                let (metadata, data) = cell.lump.get_mut();
+                (&**metadata, &**data)
+            }),
+        }
+    }
+}
@@ -0,0 +1,272 @@
+use alloc::vec::Vec;
+use core::cell::Ref;
+
+use zerocopy::FromBytes;
+
+use crate::{Bsp, LumpId};
+
+/// Index of the potentially visible set in a cluster's offset pair
+const PVS: usize = 0;
+/// Index of the potentially audible set in a cluster's offset pair
+const PAS: usize = 1;
+
+/// Error returned when reading the visibility lump
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VisibilityError {
+    /// Visibility lump is too short to contain its offset table
+    Truncated,
+    /// Cluster's compressed row starts outside of the visibility lump
+    OffsetOutOfBounds {
+        /// Cluster index
+        cluster: usize,
+    },
+}
+
+impl core::fmt::Display for VisibilityError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Truncated => f.write_str("visibility lump is truncated"),
+            Self::OffsetOutOfBounds { cluster } => {
+                write!(f, "visibility row of cluster {cluster} is out of bounds")
+            }
+        }
+    }
+}
+
+impl core::error::Error for VisibilityError {}
+
+/// Cluster visibility ([`LumpId::Visibility`])
+///
+/// Each cluster has a run-length encoded bit vector of the clusters potentially visible
+/// (and audible) from it. Rows are decoded on demand.
+pub struct Visibility<'b> {
+    data: Ref<'b, [u8]>,
+    /// Byte offsets of each cluster's (PVS, PAS) rows
+    offsets: Vec<[usize; 2]>,
+}
+
+impl Visibility<'_> {
+    /// Number of clusters
+    pub fn num_clusters(&self) -> usize {
+        self.offsets.len()
+    }
+
+    /// Length in bytes of a decompressed row
+    fn row_len(&self) -> usize {
+        self.offsets.len().div_ceil(8)
+    }
+
+    /// Compressed bytes of a row, from its start to the end of the lump
+    fn row(&self, cluster: usize, kind: usize) -> Option<&[u8]> {
+        let offset = self.offsets.get(cluster)?[kind];
+        Some(&self.data[offset..])
+    }
+
+    /// Decompress a row into a bit vector of [`Visibility::num_clusters`] bits
+    fn decompress(&self, cluster: usize, kind: usize) -> Option<Vec<u8>> {
+        let len = self.row_len();
+        let mut row = self.row(cluster, kind)?;
+        let mut out = Vec::with_capacity(len);
+
+        while out.len() < len {
+            match row {
+                // A zero byte is followed by the number of zero bytes it expands to
+                [0, count, rest @ ..] => {
+                    out.resize((out.len() + *count as usize).min(len), 0);
+                    row = rest;
+                }
+                [byte, rest @ ..] => {
+                    out.push(*byte);
+                    row = rest;
+                }
+                [] => break,
+            }
+        }
+
+        // Treat a truncated row as not visible
+        out.resize(len, 0);
+        Some(out)
+    }
+
+    /// Test a single bit of a row without decompressing the whole row
+    fn test(&self, from: usize, to: usize, kind: usize) -> bool {
+        if to >= self.num_clusters() {
+            return false;
+        }
+        let Some(mut row) = self.row(from, kind) else {
+            return false;
+        };
+
+        let target = to / 8;
+        let mut pos = 0;
+        loop {
+            match row {
+                [0, count, rest @ ..] => {
+                    pos += *count as usize;
+                    if pos > target {
+                        return false;
+                    }
+                    row = rest;
+                }
+                [byte, rest @ ..] => {
+                    if pos == target {
+                        return byte & (1 << (to % 8)) != 0;
+                    }
+                    pos += 1;
+                    row = rest;
+                }
+                [] => return false,
+            }
+        }
+    }
+
+    /// Decompressed potentially visible set of `cluster`, one bit per cluster
+    pub fn pvs(&self, cluster: usize) -> Option<Vec<u8>> {
+        self.decompress(cluster, PVS)
+    }
+
+    /// Decompressed potentially audible set of `cluster`, one bit per cluster
+    pub fn pas(&self, cluster: usize) -> Option<Vec<u8>> {
+        self.decompress(cluster, PAS)
+    }
+
+    /// Whether `to_cluster` is potentially visible from `from_cluster`
+    pub fn can_see(&self, from_cluster: usize, to_cluster: usize) -> bool {
+        self.test(from_cluster, to_cluster, PVS)
+    }
+
+    /// Whether `to_cluster` is potentially audible from `from_cluster`
+    pub fn can_hear(&self, from_cluster: usize, to_cluster: usize) -> bool {
+        self.test(from_cluster, to_cluster, PAS)
+    }
+}
+
+impl Bsp<'_> {
+    /// Read the cluster offset table of [`LumpId::Visibility`]
+    pub fn visibility(&self) -> Result<Visibility<'_>, VisibilityError> {
+        let (_, data) = self.lump(LumpId::Visibility);
+        let data = Ref::map(data, |v| &**v);
+        if data.is_empty() {
+            return Ok(Visibility {
+                data,
+                offsets: Vec::new(),
+            });
+        }
+
+        let (count, rest) = i32::read_from_prefix(&data).map_err(|_| VisibilityError::Truncated)?;
+        let count = usize::try_from(count).map_err(|_| VisibilityError::Truncated)?;
+        let table = count
+            .checked_mul(size_of::<[i32; 2]>())
+            .and_then(|len| rest.get(..len))
+            .ok_or(VisibilityError::Truncated)?;
+
+        let offsets = table
+            .chunks_exact(size_of::<[i32; 2]>())
+            .enumerate()
+            .map(|(cluster, chunk)| {
+                let pair =
+                    <[i32; 2]>::read_from_bytes(chunk).map_err(|_| VisibilityError::Truncated)?;
+                let offset = |ofs: i32| usize::try_from(ofs).ok().filter(|&ofs| ofs <= data.len());
+                offset(pair[PVS])
+                    .zip(offset(pair[PAS]))
+                    .map(|(pvs, pas)| [pvs, pas])
+                    .ok_or(VisibilityError::OffsetOutOfBounds { cluster })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Visibility { data, offsets })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+    use crate::{BspBuilder, LumpMetadata};
+
+    const CLUSTERS: usize = 20;
+
+    /// Visibility lump of 20 clusters, whose rows are 3 bytes once decompressed
+    fn vis_lump() -> Vec<u8> {
+        let table_len = 4 + CLUSTERS * 8;
+        let rows: [&[u8]; 4] = [
+            // Clusters 0 and 2, then a run of 2 zero bytes
+            &[0b101, 0, 2],
+            // A run of 2 zero bytes, then cluster 19
+            &[0, 2, 0b1000],
+            // Run longer than the row
+            &[0, 200],
+            // Last byte starts a run whose count is cut off by the end of the lump
+            &[0xff, 0],
+        ];
+        let mut offsets = vec![];
+        let mut data = vec![];
+        for row in rows {
+            offsets.push(table_len + data.len());
+            data.extend_from_slice(row);
+        }
+
+        let mut lump = (CLUSTERS as i32).to_le_bytes().to_vec();
+        for cluster in 0..CLUSTERS {
+            // Clusters 0, 1, 2 and 3 use rows 0, 1, 3 and 2, the rest reuse row 0
+            let offset = match cluster {
+                2 => offsets[3],
+                3 => offsets[2],
+                1 => offsets[1],
+                _ => offsets[0],
+            } as i32;
+            lump.extend_from_slice(&offset.to_le_bytes());
+            lump.extend_from_slice(&offset.to_le_bytes());
+        }
+        lump.extend_from_slice(&data);
+        lump
+    }
+
+    fn bsp_with_vis(lump: Vec<u8>) -> Bsp<'static> {
+        let metadata = LumpMetadata {
+            version: 0,
+            identifier: [0; 4],
+        };
+        BspBuilder::new(*b"VBSP", 20)
+            .set_lump(LumpId::Visibility, metadata, lump)
+            .build()
+    }
+
+    #[test]
+    fn decode_rows() {
+        let bsp = bsp_with_vis(vis_lump());
+        let vis = bsp.visibility().unwrap();
+        assert_eq!(vis.num_clusters(), CLUSTERS);
+
+        assert_eq!(vis.pvs(0).unwrap(), [0b101, 0, 0]);
+        assert_eq!(vis.pas(0).unwrap(), [0b101, 0, 0]);
+        assert_eq!(vis.pvs(1).unwrap(), [0, 0, 0b1000]);
+        assert_eq!(vis.pvs(3).unwrap(), [0, 0, 0]);
+        assert_eq!(vis.pvs(CLUSTERS), None);
+
+        assert!(vis.can_see(0, 0));
+        assert!(!vis.can_see(0, 1));
+        assert!(vis.can_see(0, 2));
+        assert!(vis.can_see(1, 19));
+        assert!(!vis.can_see(1, 3));
+        assert!(!vis.can_see(3, 19));
+        assert!(vis.can_hear(1, 19));
+    }
+
+    #[test]
+    fn truncated_run() {
+        let bsp = bsp_with_vis(vis_lump());
+        let vis = bsp.visibility().unwrap();
+        assert_eq!(vis.pvs(2).unwrap(), [0xff, 0, 0]);
+        assert!(vis.can_see(2, 7));
+        assert!(!vis.can_see(2, 8));
+        assert!(!vis.can_see(2, 19));
+    }
+
+    #[test]
+    fn truncated_table() {
+        let bsp = bsp_with_vis(vec![5, 0, 0, 0, 1, 0]);
+        assert!(matches!(bsp.visibility(), Err(VisibilityError::Truncated)));
+    }
+}